@@ -0,0 +1,146 @@
+use rand::Rng;
+
+use error::WolfriskError;
+use super::{NumArmies, GameRng};
+
+// a single die, 1 to 6
+pub type Die = u8;
+
+// How one round of dice between an attacker and a defender went.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BattleResult {
+    pub attacker_losses: NumArmies,
+    pub defender_losses: NumArmies,
+    // the dice rolled, highest first. empty if the resolver doesn't roll any
+    pub attacker_dice: Vec<Die>,
+    pub defender_dice: Vec<Die>,
+}
+
+// Decides the outcome of one round of a battle, with `attacking` armies (1 to
+// 3) against `defending` armies (1 or 2). Any other number of armies is an
+// `InvalidBattle`.
+pub trait CombatResolver {
+    fn resolve(&self,
+               attacking: NumArmies,
+               defending: NumArmies,
+               rng: &mut GameRng)
+               -> Result<BattleResult, WolfriskError>;
+}
+
+fn check_battle(attacking: NumArmies, defending: NumArmies) -> Result<(), WolfriskError> {
+    if !(1..=3).contains(&attacking) || !(1..=2).contains(&defending) {
+        Err(WolfriskError::InvalidBattle { attacking, defending })
+    } else {
+        Ok(())
+    }
+}
+
+
+// odds from https://www.kent.ac.uk/smsas/personal/odl/riskfaq.htm#3.2
+fn one_rolled_1(attacker: NumArmies, defender: NumArmies) -> Option<[f64; 2]> {
+    match (attacker, defender) {
+        (1, 1) => Some([0.5833, 0.4167]),
+        (2, 1) => Some([0.4213, 0.5787]),
+        (3, 1) => Some([0.3403, 0.6597]),
+        (1, 2) => Some([0.7454, 0.2546]),
+        _ => None,
+    }
+}
+
+// odds from https://www.kent.ac.uk/smsas/personal/odl/riskfaq.htm#3.2
+fn both_rolled_at_least_2(attacker: NumArmies, defender: NumArmies) -> Option<[f64; 3]> {
+    match (attacker, defender) {
+        (2, 2) => Some([0.4483, 0.2276, 0.3241]),
+        (3, 2) => Some([0.2926, 0.3717, 0.3358]),
+        _ => None,
+    }
+}
+
+// Resolves a battle with a single uniform draw against tables of the odds of
+// each outcome, without rolling any dice.
+pub struct TableResolver;
+
+impl CombatResolver for TableResolver {
+    fn resolve(&self,
+               attacking: NumArmies,
+               defending: NumArmies,
+               rng: &mut GameRng)
+               -> Result<BattleResult, WolfriskError> {
+        check_battle(attacking, defending)?;
+        let invalid_battle = WolfriskError::InvalidBattle { attacking, defending };
+
+        let roll = rng.gen_range(0., 1.);
+        let (attacker_losses, defender_losses) =
+            if defending == 1 || attacking == 1 {
+                let dist = one_rolled_1(attacking, defending).ok_or(invalid_battle)?;
+                if roll <= dist[0] {
+                    // attacker loses 1
+                    (1, 0)
+                } else {
+                    // defender loses 1
+                    (0, 1)
+                }
+            } else {
+                let dist = both_rolled_at_least_2(attacking, defending).ok_or(invalid_battle)?;
+                if roll <= dist[0] {
+                    // attacker loses 2
+                    (2, 0)
+                } else if roll > dist[0] && roll <= (dist[0] + dist[1]) {
+                    // defender loses 2
+                    (0, 2)
+                } else {
+                    // both lose 1
+                    (1, 1)
+                }
+            };
+
+        Ok(BattleResult {
+            attacker_losses,
+            defender_losses,
+            attacker_dice: Vec::new(),
+            defender_dice: Vec::new(),
+        })
+    }
+}
+
+
+// Resolves a battle by rolling a die for every army on both sides. The highest
+// dice of each side are compared in pairs, and the side with the lower die of
+// a pair loses an army. The defender wins ties.
+pub struct DiceResolver;
+
+fn roll_dice(rng: &mut GameRng, count: NumArmies) -> Vec<Die> {
+    let mut dice: Vec<Die> = (0..count).map(|_| rng.gen_range(1, 7)).collect();
+    dice.sort_by(|a, b| b.cmp(a));
+    dice
+}
+
+impl CombatResolver for DiceResolver {
+    fn resolve(&self,
+               attacking: NumArmies,
+               defending: NumArmies,
+               rng: &mut GameRng)
+               -> Result<BattleResult, WolfriskError> {
+        check_battle(attacking, defending)?;
+
+        let attacker_dice = roll_dice(rng, attacking);
+        let defender_dice = roll_dice(rng, defending);
+
+        let mut attacker_losses = 0;
+        let mut defender_losses = 0;
+        for (attacker, defender) in attacker_dice.iter().zip(defender_dice.iter()) {
+            if attacker > defender {
+                defender_losses += 1;
+            } else {
+                attacker_losses += 1;
+            }
+        }
+
+        Ok(BattleResult {
+            attacker_losses,
+            defender_losses,
+            attacker_dice,
+            defender_dice,
+        })
+    }
+}
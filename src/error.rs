@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{PlayerId, TerritoryId, NumArmies, CardId};
+
+// Something the engine was asked to do that doesn't fit the state of the game.
+// These are returned instead of panicking, so that a misbehaving player or a
+// corrupt saved game ends a single game rather than the whole process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WolfriskError {
+    // there is no territory with this ID on the map
+    InvalidTerritory(TerritoryId),
+    // there is no player with this ID in the game
+    InvalidPlayer(PlayerId),
+    // `requested` armies were to be taken from `territory`, which only has
+    // `available`
+    InsufficientArmies { territory: TerritoryId, available: NumArmies, requested: NumArmies },
+    // `player` tried to use a card that they don't hold
+    CardNotHeld { player: PlayerId, card: CardId },
+    // a territory would hold more armies than a `NumArmies` can count
+    ArmyOverflow(TerritoryId),
+    // a card was to be drawn, but the deck and the discard pile are both empty
+    NoCardsLeft,
+    // a battle between this many attacking and defending armies can't be fought
+    InvalidBattle { attacking: NumArmies, defending: NumArmies },
+    // a board was given `territories` territories for a map with `map`
+    WrongBoardSize { territories: usize, map: usize },
+    // a game can't be played by this many players
+    InvalidPlayerCount(usize),
+    // the card hands of a restored game are for `hands` players, but
+    // `players` were given
+    HandsDontMatchPlayers { hands: usize, players: usize },
+}
+
+impl fmt::Display for WolfriskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WolfriskError::InvalidTerritory(terr) => write!(f, "there is no territory {}", terr),
+            WolfriskError::InvalidPlayer(player) => write!(f, "there is no player {}", player),
+            WolfriskError::InsufficientArmies { territory, available, requested } => {
+                write!(f, "can't take {} armies from territory {}, which has {}",
+                       requested,
+                       territory,
+                       available)
+            }
+            WolfriskError::CardNotHeld { player, card } => {
+                write!(f, "player {} doesn't hold card {}", player, card)
+            }
+            WolfriskError::ArmyOverflow(terr) => write!(f, "too many armies on territory {}", terr),
+            WolfriskError::NoCardsLeft => write!(f, "there are no cards left to draw"),
+            WolfriskError::InvalidBattle { attacking, defending } => {
+                write!(f, "{} armies can't attack {} armies", attacking, defending)
+            }
+            WolfriskError::WrongBoardSize { territories, map } => {
+                write!(f, "board has {} territories but its map has {}", territories, map)
+            }
+            WolfriskError::InvalidPlayerCount(n) => write!(f, "a game can't have {} players", n),
+            WolfriskError::HandsDontMatchPlayers { hands, players } => {
+                write!(f, "the restored game has {} players, but {} were given", hands, players)
+            }
+        }
+    }
+}
+
+impl Error for WolfriskError {}
+
+
+// Why the engine refused an action a player chose. The player is told the
+// reason through `Player::action_rejected` and asked again.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rejection {
+    // the player has 5 or more cards, so they have to trade in a set
+    TradeRequired,
+    // the three cards don't form a set
+    NotASet,
+    // the player doesn't hold this card
+    CardNotHeld(CardId),
+    // the same card was offered more than once
+    DuplicateCard(CardId),
+    // there is no territory with this ID on the map
+    InvalidTerritory(TerritoryId),
+    // the player doesn't own this territory
+    NotOwner(TerritoryId),
+    // the territory was drafted by another player already
+    AlreadyClaimed(TerritoryId),
+    // the target of an attack is the player's own territory
+    NotAnEnemy(TerritoryId),
+    NotAdjacent { from: TerritoryId, to: TerritoryId },
+    // there is no path between the territories through the player's own
+    NotConnected { from: TerritoryId, to: TerritoryId },
+    // more armies were used than `allowed`
+    TooManyArmies { allowed: NumArmies, requested: NumArmies },
+    // fewer armies were used than `required`
+    TooFewArmies { required: NumArmies, requested: NumArmies },
+    // an attack has to use at least one army
+    NoArmies,
+    // the reinforcements placed don't add up to the number available
+    WrongTotal { expected: NumArmies, actual: NumArmies },
+    // the action can't be taken at this point of the turn
+    WrongStage,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Rejection::TradeRequired => write!(f, "you hold too many cards and have to trade"),
+            Rejection::NotASet => write!(f, "those cards don't form a set"),
+            Rejection::CardNotHeld(card) => write!(f, "you don't hold card {}", card),
+            Rejection::DuplicateCard(card) => write!(f, "card {} was used more than once", card),
+            Rejection::InvalidTerritory(terr) => write!(f, "there is no territory {}", terr),
+            Rejection::NotOwner(terr) => write!(f, "you don't own territory {}", terr),
+            Rejection::AlreadyClaimed(terr) => write!(f, "territory {} has been claimed", terr),
+            Rejection::NotAnEnemy(terr) => write!(f, "territory {} is your own", terr),
+            Rejection::NotAdjacent { from, to } => {
+                write!(f, "territory {} doesn't border territory {}", from, to)
+            }
+            Rejection::NotConnected { from, to } => {
+                write!(f, "territory {} can't be reached from territory {}", to, from)
+            }
+            Rejection::TooManyArmies { allowed, requested } => {
+                write!(f, "{} armies were used, but only {} are allowed", requested, allowed)
+            }
+            Rejection::TooFewArmies { required, requested } => {
+                write!(f, "{} armies were used, but at least {} are required", requested, required)
+            }
+            Rejection::NoArmies => write!(f, "at least one army has to attack"),
+            Rejection::WrongTotal { expected, actual } => {
+                write!(f, "{} armies were placed instead of {}", actual, expected)
+            }
+            Rejection::WrongStage => write!(f, "that can't be done at this point of the turn"),
+        }
+    }
+}
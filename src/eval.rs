@@ -0,0 +1,133 @@
+use board::{GameBoard, UNCLAIMED};
+use rules::Rules;
+use super::{PlayerId, TerritoryId};
+
+// Judges how good a position is for each player. Higher scores are better;
+// scores are only meant to be compared between players on the same board, or
+// for the same player between boards.
+pub trait Evaluator {
+    fn score(&self, board: &dyn GameBoard, player: PlayerId) -> f64;
+
+    // the score of each of players 0 to `num_players - 1`
+    fn scores(&self, board: &dyn GameBoard, num_players: usize) -> Vec<f64> {
+        (0..num_players).map(|player| self.score(board, player as PlayerId)).collect()
+    }
+}
+
+// How much each feature of a position counts for in a `WeightedEvaluator`.
+// Every feature is a share between 0 and 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalWeights {
+    // the share of the territories the player holds
+    pub territories: f64,
+    // the share of the armies on the board that are the player's
+    pub armies: f64,
+    // progress towards holding continents: for every continent, the square of
+    // the share of it the player holds, weighted by its bonus
+    pub continents: f64,
+    // the enemy armies next to the player's territories, as a share of all
+    // the armies on the board. counts against the player
+    pub exposure: f64,
+    // the player's share of the reinforcements everyone would get next turn
+    pub income: f64,
+}
+
+impl Default for EvalWeights {
+    fn default() -> EvalWeights {
+        EvalWeights {
+            territories: 1.,
+            armies: 1.,
+            continents: 1.5,
+            exposure: 0.5,
+            income: 2.,
+        }
+    }
+}
+
+// Scores a position as a weighted sum of simple features.
+pub struct WeightedEvaluator {
+    pub weights: EvalWeights,
+    // for working out reinforcements
+    pub rules: Rules,
+}
+
+impl Default for WeightedEvaluator {
+    fn default() -> WeightedEvaluator {
+        WeightedEvaluator::new(Rules::default())
+    }
+}
+
+impl WeightedEvaluator {
+    pub fn new(rules: Rules) -> WeightedEvaluator {
+        WeightedEvaluator {
+            weights: EvalWeights::default(),
+            rules,
+        }
+    }
+
+    pub fn with_weights(rules: Rules, weights: EvalWeights) -> WeightedEvaluator {
+        WeightedEvaluator { weights, rules }
+    }
+}
+
+fn share(part: f64, whole: f64) -> f64 {
+    if whole > 0. { part / whole } else { 0. }
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn score(&self, board: &dyn GameBoard, player: PlayerId) -> f64 {
+        if board.player_is_defeated(player) {
+            return 0.;
+        }
+
+        let num_territories = board.game_map().num_territories();
+        let territories = 0..num_territories as TerritoryId;
+        let owned = board.get_owned_territories(player);
+
+        let total_armies: f64 = territories.clone().map(|terr| board.get_num_armies(terr) as f64).sum();
+        let own_armies: f64 = owned.iter().map(|&terr| board.get_num_armies(terr) as f64).sum();
+
+        let mut continent_progress = 0.;
+        let mut total_bonus = 0.;
+        for continent in board.continents() {
+            let held = continent.territories
+                                .iter()
+                                .filter(|&&terr| board.get_owner(terr) == player)
+                                .count();
+            let held = share(held as f64, continent.territories.len() as f64);
+            continent_progress += held * held * continent.bonus as f64;
+            total_bonus += continent.bonus as f64;
+        }
+
+        // every enemy territory next to the player counts once, however many
+        // of the player's territories it borders
+        let exposed: f64 = territories.clone()
+                                      .filter(|&terr| board.is_enemy_territory(player, terr))
+                                      .filter(|&terr| {
+                                          board.game_map()
+                                               .get_neighbors(terr)
+                                               .iter()
+                                               .any(|&n| board.get_owner(n) == player)
+                                      })
+                                      .map(|terr| board.get_num_armies(terr) as f64)
+                                      .sum();
+
+        // everyone who still holds territories would get reinforcements
+        let mut owners: Vec<PlayerId> = territories.map(|terr| board.get_owner(terr))
+                                                   .filter(|&owner| owner != UNCLAIMED)
+                                                   .collect();
+        owners.sort();
+        owners.dedup();
+        let total_income: f64 = owners.iter()
+                                      .map(|&owner| board.get_territory_reinforcements(owner, &self.rules) as f64)
+                                      .sum();
+        let income = board.get_territory_reinforcements(player, &self.rules) as f64;
+
+        let w = &self.weights;
+        w.territories * share(owned.len() as f64, num_territories as f64) +
+        w.armies * share(own_armies, total_armies) +
+        w.continents * share(continent_progress, total_bonus) -
+        w.exposure * share(exposed, total_armies) +
+        w.income * share(income, total_income)
+    }
+}
@@ -0,0 +1,120 @@
+use serde_json;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use combat::Die;
+use error::Rejection;
+use map::MapDefinition;
+use observer::Phase;
+use super::{PlayerId, TerritoryId, NumArmies, Card};
+
+// Everything that happens in a game, in the order it happens. A game's events,
+// starting with its `GameStarted`, are enough to rebuild the board at any point
+// of the game (see `replay::Replay`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    // the map and board a game started (or was resumed) from. `num_players`
+    // doesn't count the neutral player, whose ID is `neutral` if there is one
+    GameStarted {
+        map: MapDefinition,
+        num_players: PlayerId,
+        #[serde(default)]
+        neutral: Option<PlayerId>,
+        territories: Vec<(PlayerId, NumArmies)>,
+    },
+    // `player` drafted `territory`, placing one army on it
+    TerritoryClaimed { player: PlayerId, territory: TerritoryId },
+    // `player` placed one of their starting armies before the first turn
+    ArmyPlaced { player: PlayerId, territory: TerritoryId },
+    TurnStarted { turn: usize, player: PlayerId },
+    // `player` moved on to `phase` of their turn
+    PhaseChanged { player: PlayerId, phase: Phase },
+    // `armies` is the number of reinforcements the set was worth.
+    // `territory_bonuses` are the extra armies placed directly on traded
+    // territories that the player owns
+    Trade {
+        player: PlayerId,
+        cards: Vec<Card>,
+        armies: NumArmies,
+        territory_bonuses: Vec<(TerritoryId, NumArmies)>,
+    },
+    Reinforced { player: PlayerId, territory: TerritoryId, armies: NumArmies },
+    BattleResolved {
+        player: PlayerId,
+        origin: TerritoryId,
+        target: TerritoryId,
+        attacker_losses: NumArmies,
+        defender_losses: NumArmies,
+        // the dice rolled, highest first. empty if the battle was resolved
+        // without rolling dice
+        #[serde(default)]
+        attacker_dice: Vec<Die>,
+        #[serde(default)]
+        defender_dice: Vec<Die>,
+    },
+    // a blitz from `origin` on `target` ended after `rounds` rounds, each of
+    // which has its own `BattleResolved`
+    BlitzResolved {
+        player: PlayerId,
+        origin: TerritoryId,
+        target: TerritoryId,
+        rounds: usize,
+        attacker_losses: NumArmies,
+        defender_losses: NumArmies,
+        conquered: bool,
+    },
+    // `player` took `target`, which was held by `previous_owner`, moving
+    // `armies` over from `origin`
+    TerritoryConquered {
+        player: PlayerId,
+        previous_owner: PlayerId,
+        origin: TerritoryId,
+        target: TerritoryId,
+        armies: NumArmies,
+    },
+    // `player` was given a card for conquering a territory this turn
+    CardDrawn { player: PlayerId, card: Card },
+    // `player` took the cards of `from`, whom they just eliminated
+    CardsCaptured { player: PlayerId, from: PlayerId, cards: Vec<Card> },
+    Fortified {
+        player: PlayerId,
+        origin: TerritoryId,
+        destination: TerritoryId,
+        armies: NumArmies,
+    },
+    PlayerEliminated { player: PlayerId, by: PlayerId },
+    // the engine refused a choice `player` made
+    ActionRejected { player: PlayerId, reason: Rejection },
+    // `player` kept making invalid choices and was put out of the game
+    PlayerForfeited { player: PlayerId },
+    // `winner` is None if the game ended without one, e.g. at the turn limit
+    GameOver { winner: Option<PlayerId>, turns: usize },
+}
+
+// Writes `events` to `path`, one JSON object per line.
+pub fn save_events<P: AsRef<Path>>(path: P, events: &[GameEvent]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    for event in events.iter() {
+        let line = serde_json::to_string(event).expect("A GameEvent can always be serialized");
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+// Reads events written by `save_events`. Blank lines are skipped.
+pub fn load_events<P: AsRef<Path>>(path: P) -> io::Result<Vec<GameEvent>> {
+    let file = BufReader::new(File::open(path)?);
+    let mut events = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(io::ErrorKind::InvalidData, err)
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
@@ -1,57 +1,390 @@
-use rand;
-use rand::Rng;
-use std::collections::{HashMap, HashSet};
+use rand::{self, Rng};
+use std::cmp::{max, min};
+use std::collections::{BTreeMap, BTreeSet};
 
 use player::Player;
-use board::{GameBoard, StandardGameBoard};
-use super::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritoryInfo};
-use super::{Trade, Reinforcement, Attack, Move, defending_allowed};
-use super::{Card, CardId, CardSymbol};
+use board::{GameBoard, GameMap, StandardGameBoard, UNCLAIMED};
+use combat::{CombatResolver, TableResolver};
+use error::{Rejection, WolfriskError};
+use event::GameEvent;
+use legal;
+use map::{MapDefinition, MapError, WorldMap, standard_map};
+use observer::{self, GameObserver, Phase};
+use rules::{FortifyRule, Rules};
+use snapshot::{GameSnapshot, SnapshotError, SNAPSHOT_VERSION};
+use state::GameState;
+use super::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories, AttackTerritoryInfo};
+use super::{Trade, Reinforcement, Attack, AttackMode, AttackOutcome, Move};
+use super::{attacking_allowed, defending_allowed};
+use super::{Card, CardId, CardSymbol, GameRng, seeded_rng};
 
 
-// odds from https://www.kent.ac.uk/smsas/personal/odl/riskfaq.htm#3.2
-fn one_rolled_1(attacker: NumArmies, defender: NumArmies) -> Option<[f64; 2]> {
-    match (attacker, defender) {
-        (1, 1) => Some([0.5833, 0.4167]),
-        (2, 1) => Some([0.4213, 0.5787]),
-        (3, 1) => Some([0.3403, 0.6597]),
-        (1, 2) => Some([0.7454, 0.2546]),
-        _ => None,
-    }
+pub struct GameManager {
+    players: Vec<Box<dyn Player>>,
+    board: Box<dyn GameBoard>,
+    rules: Rules,
+
+    // the cards available to be given to a player who conquers a territory in
+    // their turn (also the discard pile is contained in this data structure)
+    cards: CardManager,
+
+    // the number of sets traded by all players so far, for progressive set
+    // values
+    trades: usize,
+
+    curr_player: usize,
+
+    // the number of turns played so far
+    turn: usize,
+
+    // every random decision the engine makes is drawn from this
+    rng: GameRng,
+
+    // decides the outcome of every battle
+    combat: Box<dyn CombatResolver>,
+
+    // everything that has happened in the game so far
+    events: Vec<GameEvent>,
+
+    // told about every event as it happens
+    observers: Vec<Box<dyn GameObserver>>,
+
+    // how many times in a row a player may have a choice rejected before
+    // `retry_policy` kicks in
+    max_retries: u32,
+    retry_policy: RetryPolicy,
+
+    // players who have forfeited the game. they keep their territories but
+    // don't take any more turns
+    forfeited: BTreeSet<PlayerId>,
+
+    // the ID of the neutral player, if there is one. it comes after the real
+    // players, holds territories and defends them, but never takes a turn
+    neutral: Option<PlayerId>,
+
+    // whether the players still have to draft the territories and place
+    // their starting armies before the first turn
+    needs_draft: bool,
+    needs_placement: bool,
 }
 
-// odds from https://www.kent.ac.uk/smsas/personal/odl/riskfaq.htm#3.2
-fn both_rolled_at_least_2(attacker: NumArmies, defender: NumArmies) -> Option<[f64; 3]> {
-    match (attacker, defender) {
-        (2, 2) => Some([0.4483, 0.2276, 0.3241]),
-        (3, 2) => Some([0.2926, 0.3717, 0.3358]),
-        _ => None,
-    }
+// How the territories are handed out at the start of a new game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SetupMode {
+    // as equally as possible, at random
+    Random,
+    // the players claim them one at a time, in turn order, until none are left
+    Draft,
 }
 
+// What happens to a player who keeps making choices that are rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RetryPolicy {
+    // the engine makes the choice for them: the first set they hold if they
+    // have to trade, otherwise no trade; all reinforcements on their
+    // lowest-numbered territory; no more attacks; no fortification
+    DefaultAction,
+    // the player forfeits the game
+    Forfeit,
+}
 
-pub struct GameManager {
-    players: Vec<Box<Player>>,
-    board: Box<GameBoard>,
+// by default a player may have a choice rejected this many times in a row
+pub const DEFAULT_MAX_RETRIES: u32 = 10;
 
-    // the cards available to be given to a player who conquers a territory in
-    // their turn (also the discard pile is contained in this data structure)
-    cards: CardManager,
+// the number of armies each player starts the game with: 35, 30, 25 and 20
+// for 3, 4, 5 and 6 players, and 40 in a two-player game
+pub fn starting_armies(num_players: usize) -> NumArmies {
+    match num_players {
+        0..=2 => 40,
+        3..=6 => 50 - 5 * num_players as NumArmies,
+        _ => 20,
+    }
+}
 
+// Builds a `GameManager`. Any setting that isn't supplied falls back to the
+// standard game: the classic world map randomly distributed on a
+// `StandardGameBoard`, and the standard deck of cards for that map. If no seed
+// is given, one is drawn from the thread RNG.
+pub struct GameManagerBuilder {
+    players: Vec<Box<dyn Player>>,
+    board: Option<Box<dyn GameBoard>>,
+    map: Option<WorldMap>,
+    rules: Rules,
+    rng: Option<GameRng>,
+    combat: Box<dyn CombatResolver>,
+    observers: Vec<Box<dyn GameObserver>>,
+    max_retries: u32,
+    retry_policy: RetryPolicy,
+    setup_mode: SetupMode,
+    initial_placement: bool,
+    neutral_player: Option<bool>,
+
+    // the rest of the state of a restored game
+    cards: Option<CardManager>,
+    trades: usize,
     curr_player: usize,
+    turn: usize,
+    forfeited: BTreeSet<PlayerId>,
 }
 
-impl GameManager {
-    pub fn new_game(players: Vec<Box<Player>>) -> GameManager {
-        let num_players = players.len();
-        let board = StandardGameBoard::randomly_distributed(num_players as u8);
-
-        GameManager {
-            players: players,
-            board: Box::new(board),
-            cards: CardManager::standard_card_manager(num_players),
+impl Default for GameManagerBuilder {
+    fn default() -> GameManagerBuilder {
+        GameManagerBuilder::new()
+    }
+}
+
+impl GameManagerBuilder {
+    pub fn new() -> GameManagerBuilder {
+        GameManagerBuilder {
+            players: Vec::new(),
+            board: None,
+            map: None,
+            rules: Rules::default(),
+            rng: None,
+            combat: Box::new(TableResolver),
+            observers: Vec::new(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_policy: RetryPolicy::DefaultAction,
+            setup_mode: SetupMode::Random,
+            initial_placement: true,
+            neutral_player: None,
+            cards: None,
+            trades: 0,
             curr_player: 0,
+            turn: 0,
+            forfeited: BTreeSet::new(),
+        }
+    }
+
+    // adds a single player. players take their turns in the order they were added
+    pub fn player(mut self, player: Box<dyn Player>) -> GameManagerBuilder {
+        self.players.push(player);
+        self
+    }
+
+    pub fn players(mut self, players: Vec<Box<dyn Player>>) -> GameManagerBuilder {
+        self.players.extend(players);
+        self
+    }
+
+    // use the given board instead of a randomly distributed standard board
+    pub fn board(mut self, board: Box<dyn GameBoard>) -> GameManagerBuilder {
+        self.board = Some(board);
+        self
+    }
+
+    // randomly distribute the territories of `map` instead of the classic
+    // world map. ignored if a board is given
+    pub fn map(mut self, map: WorldMap) -> GameManagerBuilder {
+        self.map = Some(map);
+        self
+    }
+
+    // play by `rules` instead of the classic rules. a restored game plays by
+    // the rules saved with it
+    pub fn rules(mut self, rules: Rules) -> GameManagerBuilder {
+        self.rules = rules;
+        self
+    }
+
+    // seed the game's RNG. the same seed and the same player decisions give
+    // the same game
+    pub fn seed(self, seed: u64) -> GameManagerBuilder {
+        self.rng(seeded_rng(seed))
+    }
+
+    pub fn rng(mut self, rng: GameRng) -> GameManagerBuilder {
+        self.rng = Some(rng);
+        self
+    }
+
+    // resolve battles with `combat` instead of the odds tables of
+    // `TableResolver`
+    pub fn combat_resolver(mut self, combat: Box<dyn CombatResolver>) -> GameManagerBuilder {
+        self.combat = combat;
+        self
+    }
+
+    // adds an observer. it is told about the game from its `GameStarted`
+    // event onwards
+    pub fn observer(mut self, observer: Box<dyn GameObserver>) -> GameManagerBuilder {
+        self.observers.push(observer);
+        self
+    }
+
+    // how many times in a row a player may have a choice rejected before
+    // `retry_policy` applies. 0 means the first rejection is final
+    pub fn max_retries(mut self, max_retries: u32) -> GameManagerBuilder {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> GameManagerBuilder {
+        self.retry_policy = policy;
+        self
+    }
+
+    // how the territories of the map are handed out. ignored if a board is
+    // given or a game is restored
+    pub fn setup_mode(mut self, mode: SetupMode) -> GameManagerBuilder {
+        self.setup_mode = mode;
+        self
+    }
+
+    // whether a new game gets a neutral player, as in the official two-player
+    // game. the neutral player takes a share of the territories and starting
+    // armies, with the real players placing its armies for it, but never
+    // takes a turn. by default there is one if there are exactly two players
+    // and the builder deals the board itself. ignored if a game is restored
+    pub fn neutral_player(mut self, enabled: bool) -> GameManagerBuilder {
+        self.neutral_player = Some(enabled);
+        self
+    }
+
+    // whether the players place their starting armies (see `starting_armies`)
+    // one at a time before the first turn. on by default. armies already on
+    // the board count towards a player's starting armies
+    pub fn initial_placement(mut self, enabled: bool) -> GameManagerBuilder {
+        self.initial_placement = enabled;
+        self
+    }
+
+    // continue the game saved in `snapshot` rather than starting a new one.
+    // the players have to be supplied before this, in the same order as in the
+    // saved game. the state of the saved game's RNG is not part of the snapshot, so
+    // seed this builder to make the rest of the game reproducible
+    pub fn restore(mut self, snapshot: GameSnapshot) -> Result<GameManagerBuilder, SnapshotError> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
         }
+
+        if self.players.len() != snapshot.num_players as usize {
+            return Err(SnapshotError::Inconsistent(format!("the game has {} players, but {} were given",
+                                                           snapshot.num_players,
+                                                           self.players.len())));
+        }
+
+        let map = WorldMap::from_definition(&snapshot.map)?;
+        let board_players = snapshot.num_players + snapshot.neutral.map_or(0, |_| 1);
+        if snapshot.territories.iter().any(|&(owner, _)| owner >= board_players) ||
+           snapshot.neutral.is_some_and(|neutral| neutral != snapshot.num_players) ||
+           snapshot.forfeited.iter().any(|&player| player >= snapshot.num_players) ||
+           snapshot.curr_player >= snapshot.num_players ||
+           snapshot.cards.num_players() != snapshot.num_players as usize {
+            return Err(SnapshotError::Inconsistent(format!("state doesn't match the {} players",
+                                                           snapshot.num_players)));
+        }
+        if let Some(terr) = snapshot.territories.iter().position(|&(_, armies)| armies == 0) {
+            return Err(SnapshotError::Inconsistent(format!("territory {} has no armies", terr)));
+        }
+        snapshot.cards.check_consistency(map.num_territories())?;
+
+        let board = StandardGameBoard::new(board_players, snapshot.territories, map)
+            .map_err(|err| SnapshotError::Inconsistent(err.to_string()))?;
+        self.board = Some(Box::new(board));
+        self.rules = snapshot.rules;
+        self.neutral_player = Some(snapshot.neutral.is_some());
+        self.cards = Some(snapshot.cards);
+        self.trades = snapshot.trades;
+        self.curr_player = snapshot.curr_player as usize;
+        self.turn = snapshot.turn;
+        self.forfeited = snapshot.forfeited.into_iter().collect();
+        Ok(self)
+    }
+
+    // fails if there are no players, or more than a `PlayerId` can tell apart,
+    // or if a restored game was given a different number of players
+    pub fn build(self) -> Result<GameManager, WolfriskError> {
+        let num_players = self.players.len();
+        if num_players == 0 || num_players >= UNCLAIMED as usize {
+            return Err(WolfriskError::InvalidPlayerCount(num_players));
+        }
+        let neutral = if self.neutral_player.unwrap_or(num_players == 2 && self.board.is_none()) {
+            Some(num_players as PlayerId)
+        } else {
+            None
+        };
+        let board_players = num_players as u8 + neutral.map_or(0, |_| 1);
+        let mut rng = match self.rng {
+            Some(rng) => rng,
+            None => seeded_rng(rand::thread_rng().gen()),
+        };
+        let mut needs_draft = false;
+        let board = match self.board {
+            Some(board) => board,
+            None => {
+                let map = self.map.unwrap_or_else(standard_map);
+                match self.setup_mode {
+                    SetupMode::Random => {
+                        Box::new(StandardGameBoard::randomly_distributed(board_players,
+                                                                         map,
+                                                                         &mut rng))
+                    }
+                    SetupMode::Draft => {
+                        needs_draft = true;
+                        Box::new(StandardGameBoard::unclaimed(board_players, map))
+                    }
+                }
+            }
+        };
+        let cards = match self.cards {
+            Some(cards) => {
+                if cards.num_players() != num_players {
+                    return Err(WolfriskError::HandsDontMatchPlayers {
+                        hands: cards.num_players(),
+                        players: num_players,
+                    });
+                }
+                cards
+            }
+            None => {
+                let num_territories = board.game_map().num_territories();
+                CardManager::standard_card_manager(num_players, num_territories, &mut rng)
+            }
+        };
+
+        let mut mgr = GameManager {
+            players: self.players,
+            board,
+            rules: self.rules,
+            cards,
+            trades: self.trades,
+            curr_player: self.curr_player,
+            turn: self.turn,
+            rng,
+            combat: self.combat,
+            events: Vec::new(),
+            observers: self.observers,
+            max_retries: self.max_retries,
+            retry_policy: self.retry_policy,
+            forfeited: self.forfeited,
+            neutral,
+            needs_draft,
+            needs_placement: self.initial_placement && self.turn == 0,
+        };
+        let start = GameEvent::GameStarted {
+            map: MapDefinition::from_map(mgr.board.game_map(), mgr.board.continents()),
+            num_players: num_players as PlayerId,
+            neutral,
+            territories: mgr.board_territories(),
+        };
+        mgr.emit(start);
+        Ok(mgr)
+    }
+}
+
+
+impl GameManager {
+    pub fn new_game(players: Vec<Box<dyn Player>>) -> Result<GameManager, WolfriskError> {
+        GameManagerBuilder::new().players(players).build()
+    }
+
+    pub fn board(&self) -> &dyn GameBoard {
+        self.board.as_ref()
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
     }
 
     fn current_player(&self) -> PlayerId {
@@ -64,131 +397,468 @@ impl GameManager {
         self.curr_player as PlayerId
     }
 
-    pub fn run(&mut self) {
-        self.log_starting_game();
-        let mut current_player = self.current_player();
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
 
-        const MAX_NUM_TURNS: usize = 100;
-        let mut turn = 0;
+    // the events of this game so far, starting with `GameEvent::GameStarted`
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
 
-        while !self.board.game_is_over() {
-            if !self.board.player_is_defeated(current_player) {
-                turn += 1;
-                let trade_reinf = self.process_trade(current_player);
-                self.process_reinforcement(current_player, trade_reinf);
-                self.process_attack(current_player);
-                self.process_fortify(current_player);
+    // adds an observer to a game that is already under way. it only hears
+    // about events from now on; the earlier ones are available from `events`
+    pub fn add_observer(&mut self, observer: Box<dyn GameObserver>) {
+        self.observers.push(observer);
+    }
 
-                if turn >= MAX_NUM_TURNS {
-                    println!("MAX_NUM_TURNS exceeded, terminating game");
-                    break;
+    fn emit(&mut self, event: GameEvent) {
+        for observer in self.observers.iter_mut() {
+            observer::notify(observer.as_mut(), &event);
+        }
+        self.events.push(event);
+    }
+
+    fn enter_phase(&mut self, player: PlayerId, phase: Phase) {
+        self.emit(GameEvent::PhaseChanged { player, phase });
+    }
+
+    // the owner and number of armies of every territory
+    fn board_territories(&self) -> Vec<(PlayerId, NumArmies)> {
+        (0..self.board.game_map().num_territories())
+            .map(|i| {
+                let tid = i as TerritoryId;
+                (self.board.get_owner(tid), self.board.get_num_armies(tid))
+            })
+            .collect()
+    }
+
+    pub fn run(&mut self) -> Result<(), WolfriskError> {
+        while self.play_turn()? {}
+        Ok(())
+    }
+
+    // plays the current player's turn (if they are still in the game) and
+    // moves on to the next player. returns false once the game is over or the
+    // turn limit has been reached. the game can be saved between calls. an
+    // error means the game is in a state it can't be continued from
+    pub fn play_turn(&mut self) -> Result<bool, WolfriskError> {
+        if self.needs_draft {
+            self.needs_draft = false;
+            self.process_draft()?;
+        }
+        if self.needs_placement {
+            self.needs_placement = false;
+            self.process_placement()?;
+        }
+
+        if self.game_is_over() {
+            return Ok(false);
+        }
+
+        let current_player = self.current_player();
+        if self.is_active(current_player) {
+            self.turn += 1;
+            let turn = self.turn;
+            self.emit(GameEvent::TurnStarted { turn, player: current_player });
+            self.play_phases(current_player)?;
+        }
+        self.next_player();
+
+        if self.game_is_over() {
+            let winner = self.winner();
+            self.emit_game_over(winner);
+            Ok(false)
+        } else if self.rules.turn_limit.is_some_and(|limit| self.turn >= limit) {
+            self.emit_game_over(None);
+            Ok(false)
+        } else {
+            Ok(true)
+        }
+    }
+
+    // plays the phases of `player`'s turn, stopping early if they forfeit
+    fn play_phases(&mut self, player: PlayerId) -> Result<(), WolfriskError> {
+        self.enter_phase(player, Phase::Trade);
+        let trade_reinf = self.process_trade(player, false)?;
+        if self.forfeited.contains(&player) {
+            return Ok(());
+        }
+        self.enter_phase(player, Phase::Reinforce);
+        self.process_reinforcement(player, trade_reinf)?;
+        if self.forfeited.contains(&player) {
+            return Ok(());
+        }
+        self.enter_phase(player, Phase::Attack);
+        self.process_attack(player)?;
+        if self.forfeited.contains(&player) {
+            return Ok(());
+        }
+        self.enter_phase(player, Phase::Fortify);
+        self.process_fortify(player)
+    }
+
+    // whether `player` still takes turns
+    fn is_active(&self, player: PlayerId) -> bool {
+        !self.board.player_is_defeated(player) && !self.forfeited.contains(&player)
+    }
+
+    fn active_players(&self) -> Vec<PlayerId> {
+        (0..self.players.len() as PlayerId).filter(|&p| self.is_active(p)).collect()
+    }
+
+    // the game is over once a single player owns the whole board, or everyone
+    // else has forfeited
+    fn game_is_over(&self) -> bool {
+        self.board.game_is_over() || self.active_players().len() <= 1
+    }
+
+    fn winner(&self) -> Option<PlayerId> {
+        let active = self.active_players();
+        if active.len() == 1 {
+            Some(active[0])
+        } else {
+            None
+        }
+    }
+
+    fn emit_game_over(&mut self, winner: Option<PlayerId>) {
+        let turns = self.turn;
+        self.emit(GameEvent::GameOver { winner, turns });
+    }
+
+    // captures the state of the game, to be resumed with
+    // `GameManagerBuilder::restore`
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            version: SNAPSHOT_VERSION,
+            map: MapDefinition::from_map(self.board.game_map(), self.board.continents()),
+            rules: self.rules.clone(),
+            num_players: self.players.len() as PlayerId,
+            territories: self.board_territories(),
+            cards: self.cards.clone(),
+            trades: self.trades,
+            curr_player: self.current_player(),
+            turn: self.turn,
+            forfeited: self.forfeited.iter().cloned().collect(),
+            neutral: self.neutral,
+        }
+    }
+
+    // the state of the game as it stands between turns, with the current
+    // player about to start theirs, for looking ahead. it can only fail if
+    // the board's map can't be rebuilt from its definition
+    pub fn state(&self) -> Result<GameState, MapError> {
+        let state = GameState::new(self.board.as_ref(), self.rules.clone(), self.cards.clone())?;
+        Ok(state.with_trades(self.trades)
+                .with_forfeited(self.forfeited.clone())
+                .starting_turn(self.current_player(), self.turn))
+    }
+
+    // the players take turns claiming an unclaimed territory, placing one army
+    // on it, until every territory has been claimed. a player who has
+    // forfeited still receives territories, claimed for them by default. the
+    // neutral player, if there is one, claims its territories at random
+    fn process_draft(&mut self) -> Result<(), WolfriskError> {
+        let num_players = self.players.len() + self.neutral.map_or(0, |_| 1);
+        let mut next = self.curr_player;
+        loop {
+            let unclaimed: Vec<TerritoryId> = (0..self.board.game_map().num_territories())
+                .map(|t| t as TerritoryId)
+                .filter(|&t| self.board.get_owner(t) == UNCLAIMED)
+                .collect();
+            if unclaimed.is_empty() {
+                return Ok(());
+            }
+
+            let player = next as PlayerId;
+            let terr = if self.forfeited.contains(&player) {
+                unclaimed[0]
+            } else if self.neutral == Some(player) {
+                unclaimed[self.rng.gen_range(0, unclaimed.len())]
+            } else {
+                self.choose_claim(player, &unclaimed)
+            };
+            self.board.set_territory(terr, player, 1)?;
+            self.emit(GameEvent::TerritoryClaimed { player, territory: terr });
+            next = (next + 1) % num_players;
+        }
+    }
+
+    fn choose_claim(&mut self, player: PlayerId, unclaimed: &[TerritoryId]) -> TerritoryId {
+        let mut attempts = 0;
+        loop {
+            let terr = self.get_player(player).claim_territory(player, unclaimed, self.board.as_ref());
+            let verdict = if !self.is_territory(terr) {
+                Err(Rejection::InvalidTerritory(terr))
+            } else if !unclaimed.contains(&terr) {
+                Err(Rejection::AlreadyClaimed(terr))
+            } else {
+                Ok(())
+            };
+
+            match verdict {
+                Ok(()) => return terr,
+                Err(reason) => {
+                    if !self.reject(player, reason, &mut attempts) {
+                        // the territory is claimed for the player even if
+                        // they forfeit
+                        self.out_of_retries(player);
+                        return unclaimed[0];
+                    }
                 }
             }
-            current_player = self.next_player();
         }
     }
 
-    pub fn log_starting_game(&self) {
-        println!("Starting a game with {} players.", self.players.len());
-        println!("Deck:");
-        for card in self.cards.get_available() {
-            println!("Card: {:?}", card);
+    // the players take turns placing one army at a time on their own
+    // territories until they have all placed their starting armies. after
+    // every round, one of the real players places an army for the neutral
+    // player, if there is one and it holds any territories
+    fn process_placement(&mut self) -> Result<(), WolfriskError> {
+        let num_players = self.players.len();
+        let starting = starting_armies(num_players);
+        let board_players = num_players + self.neutral.map_or(0, |_| 1);
+        let mut remaining: Vec<NumArmies> = (0..board_players)
+            .map(|p| {
+                let on_board: NumArmies = self.board
+                                              .get_owned_territories(p as PlayerId)
+                                              .into_iter()
+                                              .map(|t| self.board.get_num_armies(t))
+                                              .sum();
+                starting.saturating_sub(on_board)
+            })
+            .collect();
+        if let Some(neutral) = self.neutral {
+            if self.board.get_owned_territories(neutral).is_empty() {
+                remaining[neutral as usize] = 0;
+            }
         }
+
+        let mut round = 0;
+        while remaining.iter().any(|&r| r > 0) {
+            for i in 0..num_players {
+                let player = ((self.curr_player + i) % num_players) as PlayerId;
+                if remaining[player as usize] == 0 {
+                    continue;
+                }
+                if !self.is_active(player) {
+                    remaining[player as usize] = 0;
+                    continue;
+                }
+
+                let left = remaining[player as usize];
+                match self.choose_placement(player, left) {
+                    Some(terr) => {
+                        self.board.add_armies(terr, 1)?;
+                        self.emit(GameEvent::ArmyPlaced { player, territory: terr });
+                        remaining[player as usize] -= 1;
+                    }
+                    None => remaining[player as usize] = 0,
+                }
+            }
+
+            if let Some(neutral) = self.neutral {
+                if remaining[neutral as usize] > 0 {
+                    let left = remaining[neutral as usize];
+                    let placers = self.active_players();
+                    let terr = if placers.is_empty() {
+                        None
+                    } else {
+                        let placer = placers[round % placers.len()];
+                        self.choose_placement_for(placer, neutral, left)
+                    };
+
+                    // if nobody can place it, it goes on the neutral player's
+                    // lowest-numbered territory
+                    match terr.or_else(|| self.board.get_owned_territories(neutral).first().cloned()) {
+                        Some(terr) => {
+                            self.board.add_armies(terr, 1)?;
+                            self.emit(GameEvent::ArmyPlaced { player: neutral, territory: terr });
+                            remaining[neutral as usize] -= 1;
+                        }
+                        None => remaining[neutral as usize] = 0,
+                    }
+                }
+            }
+            round += 1;
+        }
+        Ok(())
+    }
+
+    // asks `player` where to place their next starting army. returns None if
+    // they forfeit instead
+    fn choose_placement(&mut self, player: PlayerId, remaining: NumArmies) -> Option<TerritoryId> {
+        self.choose_placement_for(player, player, remaining)
     }
 
-    // returns the number of extra reinforcements resulting from trading cards in
-    // isn't this wrong? aren't there two different behaviors? if you are at the beginning
-    // of a turn, you can turn in as many as you want
-    // but during an attack you must turn in only until you have > 5, then you have to stop
-    fn process_trade(&mut self, player: PlayerId) -> NumArmies {
-        if self.cards.get_num_player_cards(player) < 3 {
-            return 0;
+    // asks `placer` where to place the next of `owner`'s starting armies,
+    // which has to go on one of `owner`'s territories. returns None if
+    // `placer` forfeits instead
+    fn choose_placement_for(&mut self,
+                            placer: PlayerId,
+                            owner: PlayerId,
+                            remaining: NumArmies)
+                            -> Option<TerritoryId> {
+        let mut attempts = 0;
+        loop {
+            let terr = self.get_player(placer).place_army(owner, remaining, self.board.as_ref());
+            match self.verify_owner(owner, terr) {
+                Ok(()) => return Some(terr),
+                Err(reason) => {
+                    if !self.reject(placer, reason, &mut attempts) {
+                        if !self.out_of_retries(placer) {
+                            return None;
+                        }
+                        return self.board.get_owned_territories(owner).first().cloned();
+                    }
+                }
+            }
         }
+    }
 
-        let trade_necessary = self.cards.get_num_player_cards(player) > 4;
-        let terr_reinf = self.board.get_territory_reinforcements(player);
-        let player_cards = self.cards.get_player_cards(player);
+    // returns the number of extra reinforcements resulting from trading cards in.
+    // at the beginning of a turn the player can trade in as many sets as they
+    // like, and has to while they hold `forced_trade_hand_size` cards. in the
+    // middle of an attack, after taking an eliminated player's cards, they
+    // trade only until they hold fewer than that
+    fn process_trade(&mut self, player: PlayerId, mid_attack: bool) -> Result<NumArmies, WolfriskError> {
+        let terr_reinf = self.board.get_territory_reinforcements(player, &self.rules);
 
         let mut reinf = 0;
-        loop {
+        let mut attempts = 0;
+        while self.cards.get_num_player_cards(player)? >= 3 {
+            let trade_necessary = self.cards.get_num_player_cards(player)? >=
+                                  self.rules.forced_trade_hand_size;
+            if mid_attack && !trade_necessary {
+                break;
+            }
+            let player_cards = self.cards.get_player_cards(player)?;
+            let next_value = self.rules.next_set_value(self.trades);
             let chosen_trade = self.get_player(player)
-                                   .make_trade(&player_cards[..], terr_reinf, trade_necessary);
-            if self.verify_trade(player, &chosen_trade, trade_necessary) {
-                match chosen_trade {
-                    Some(trade) => {
-                        println!("Player {} is trading in {:?}", player, trade.cards);
-                        reinf += self.perform_trade(player, trade);
+                                   .make_trade(&player_cards[..],
+                                               terr_reinf,
+                                               next_value,
+                                               trade_necessary);
+            let trade = match self.verify_trade(player, &chosen_trade, trade_necessary) {
+                Ok(()) => chosen_trade,
+                Err(reason) => {
+                    if self.reject(player, reason, &mut attempts) {
+                        continue;
                     }
-                    None => {
-                        // assume that the player doesn't want to trade in anything else
+                    if !self.out_of_retries(player) {
                         break;
                     }
+                    if trade_necessary {
+                        legal::legal_trades(&player_cards).into_iter().next()
+                    } else {
+                        None
+                    }
                 }
+            };
+            attempts = 0;
 
-                if self.cards.get_num_player_cards(player) < 3 {
+            match trade {
+                Some(trade) => {
+                    reinf = self.perform_trade(player, trade)?.saturating_add(reinf);
+                }
+                None => {
+                    // assume that the player doesn't want to trade in anything else
                     break;
                 }
-
-            } else {
-                println!("Invalid trade chosen. Choose again.");
             }
         }
-        reinf
+        Ok(reinf)
     }
 
     // returns the number of bonus armies granted by the trade-in
-    fn perform_trade(&mut self, player: PlayerId, trade: Trade) -> NumArmies {
-        for i in 0..3 {
-            self.cards.player_discard_card(player, trade.cards[i].1);
-            match trade.cards[i].0.get_territory() {
-                None => continue,
-                Some(tid) => {
+    fn perform_trade(&mut self, player: PlayerId, trade: Trade) -> Result<NumArmies, WolfriskError> {
+        let mut territory_bonuses = Vec::new();
+        let bonus = self.rules.owned_territory_bonus;
+        if bonus > 0 {
+            for &(card, _) in trade.cards.iter() {
+                if let Some(tid) = card.get_territory() {
                     if self.board.get_owner(tid) == player {
-                        self.board.add_armies(tid, 2);
+                        territory_bonuses.push((tid, bonus));
                     }
-                },
+                }
             }
         }
+        self.check_room(&territory_bonuses)?;
 
-        trade.value()
-    }
+        for &(_, cid) in trade.cards.iter() {
+            self.cards.player_discard_card(player, cid)?;
+        }
+        for &(tid, bonus) in territory_bonuses.iter() {
+            self.board.add_armies(tid, bonus)?;
+        }
 
-    pub fn process_reinforcement(&mut self, curr_id: PlayerId, trade_reinf: NumArmies) {
-        let owned = self.board.get_owned_territories(curr_id);
+        let armies = self.rules.set_value(&trade, self.trades);
+        self.trades += 1;
+        self.emit(GameEvent::Trade {
+            player,
+            cards: trade.cards.iter().map(|&(card, _)| card).collect(),
+            armies,
+            territory_bonuses,
+        });
+        Ok(armies)
+    }
 
+    pub fn process_reinforcement(&mut self,
+                                 curr_id: PlayerId,
+                                 trade_reinf: NumArmies)
+                                 -> Result<(), WolfriskError> {
         // calculate reinf
-        let reinf_amt = self.board.get_territory_reinforcements(curr_id) + trade_reinf;
+        let reinf_amt = self.board
+                            .get_territory_reinforcements(curr_id, &self.rules)
+                            .saturating_add(trade_reinf);
+        self.distribute_armies(curr_id, reinf_amt)
+    }
 
-        println!("\nPlayer {} is distributing {} reinforcements",
-                 curr_id,
-                 reinf_amt);
-        println!("==========");
+    // asks `curr_id` to place `reinf_amt` armies on their territories
+    fn distribute_armies(&mut self, curr_id: PlayerId, reinf_amt: NumArmies) -> Result<(), WolfriskError> {
+        let owned = self.board.get_owned_territories(curr_id);
 
-        loop {
+        let mut attempts = 0;
+        let chosen_reinf = loop {
             let chosen_reinf = self.get_player(curr_id)
-                                   .distrib_reinforcements(reinf_amt, &owned[..]);
-            if self.verify_reinf(curr_id, reinf_amt, &chosen_reinf) {
-                for (&terr, &reinf) in chosen_reinf.iter() {
-                    if reinf > 0 {
-                        let owner = self.board.get_owner(terr);
-                        let num_armies = self.board.get_num_armies(terr);
-                        self.board.set_territory(terr, owner, num_armies + reinf);
-                        println!("  territory {} gained {} units (now {} in total)",
-                                 terr,
-                                 reinf,
-                                 self.board.get_num_armies(terr));
+                                   .distrib_reinforcements(reinf_amt, &owned[..], self.board.as_ref());
+            match self.verify_reinf(curr_id, reinf_amt, &chosen_reinf) {
+                Ok(()) => break chosen_reinf,
+                Err(reason) => {
+                    if self.reject(curr_id, reason, &mut attempts) {
+                        continue;
                     }
+                    if !self.out_of_retries(curr_id) {
+                        return Ok(());
+                    }
+                    let mut reinf = BTreeMap::new();
+                    if let Some(&terr) = owned.first() {
+                        reinf.insert(terr, reinf_amt);
+                    }
+                    break Reinforcement::new(reinf);
                 }
-                break;
-            } else {
-                println!("Invalid reinforcement chosen. Choose again.");
+            }
+        };
+
+        let placements: Vec<_> = chosen_reinf.iter().map(|(&terr, &reinf)| (terr, reinf)).collect();
+        self.check_room(&placements)?;
+        for (terr, reinf) in placements {
+            if reinf > 0 {
+                self.board.add_armies(terr, reinf)?;
+                self.emit(GameEvent::Reinforced {
+                    player: curr_id,
+                    territory: terr,
+                    armies: reinf,
+                });
             }
         }
+        Ok(())
     }
 
-    fn make_attack_info(&self, player: PlayerId) -> HashMap<TerritoryId, AttackTerritoryInfo> {
+    fn make_attack_info(&self, player: PlayerId) -> AttackTerritories {
         let owned = self.board.get_owned_territories(player);
-        let mut attack_info = HashMap::new();
+        let mut attack_info = BTreeMap::new();
         for &terr in owned.iter() {
             let ati = AttackTerritoryInfo {
                 id: terr,
@@ -206,15 +876,14 @@ impl GameManager {
     }
 
     fn update_attack_info(&mut self,
-                          attack_info: &mut HashMap<TerritoryId, AttackTerritoryInfo>,
+                          attack_info: &mut AttackTerritories,
                           origin: TerritoryId,
                           target: TerritoryId,
                           conquered: bool) {
         // update number of armies for the attacking territory
         if self.board.get_num_armies(origin) == 1 {
             attack_info.remove(&origin);
-        } else {
-            let origin_ati = attack_info.get_mut(&origin).unwrap();
+        } else if let Some(origin_ati) = attack_info.get_mut(&origin) {
             origin_ati.armies = self.board.get_num_armies(origin);
         }
 
@@ -227,35 +896,53 @@ impl GameManager {
         }
     }
 
-    pub fn process_attack(&mut self, player: PlayerId) {
+    pub fn process_attack(&mut self, player: PlayerId) -> Result<(), WolfriskError> {
         // prompt the player for a sequence of attacks:
         let mut attack_info = self.make_attack_info(player);
 
         let mut conquered_one = false;
+        let mut attempts = 0;
 
         loop {
-            let chosen_attack = self.get_player(player).make_attack(&attack_info);
+            let chosen_attack = self.get_player(player).make_attack(&attack_info, self.board.as_ref());
             match chosen_attack {
                 None => break,
                 Some(attack) => {
-                    println!("Player {} is attacking with {} from {} to {}",
-                             player,
-                             attack.amount_attacking,
-                             attack.origin,
-                             attack.target);
-                    if self.verify_battle(player, &attack) {
-                        let conquered = self.perform_battle(&attack);
-
-                        self.update_attack_info(&mut attack_info,
-                                                attack.origin,
-                                                attack.target,
-                                                conquered);
-
-                        if conquered {
-                            conquered_one = true;
+                    match self.verify_battle(player, &attack) {
+                        Ok(()) => {
+                            attempts = 0;
+                            let defender = self.board.get_owner(attack.target);
+                            let outcome = self.perform_attack(player, &attack)?;
+                            self.get_player(player).attack_resolved(&attack, &outcome);
+                            let conquered = outcome.conquered;
+
+                            if conquered && self.board.player_is_defeated(defender) {
+                                // the player may have placed armies from a
+                                // trade, so start over
+                                attack_info = self.make_attack_info(player);
+                            } else {
+                                self.update_attack_info(&mut attack_info,
+                                                        attack.origin,
+                                                        attack.target,
+                                                        conquered);
+                            }
+
+                            if conquered {
+                                conquered_one = true;
+                            }
+                            if self.forfeited.contains(&player) {
+                                return Ok(());
+                            }
+                        }
+                        Err(reason) => {
+                            if !self.reject(player, reason, &mut attempts) {
+                                // the default is to stop attacking
+                                if !self.out_of_retries(player) {
+                                    return Ok(());
+                                }
+                                break;
+                            }
                         }
-                    } else {
-                        println!("Attack chosen is invalid. Choose again");
                     }
                 }
             }
@@ -263,168 +950,334 @@ impl GameManager {
         }
 
         if conquered_one {
-            self.cards.draw_random_for_player(player);
+            match self.cards.draw_random_for_player(player, &mut self.rng) {
+                Ok(card) => self.emit(GameEvent::CardDrawn { player, card }),
+                // with every card held, the player just goes without
+                Err(WolfriskError::NoCardsLeft) => {}
+                Err(err) => return Err(err),
+            }
         }
+        Ok(())
     }
 
-    // this function is called once the proposed attack has been verified
-    // to be a valid attack
-    // Returns true if the battle resulted in the defending territory being
-    // conquered
-    fn perform_battle(&mut self, attack: &Attack) -> bool {
+    // fights `attack`, which has been verified to be valid: one round, or for
+    // a blitz as many as it takes
+    fn perform_attack(&mut self, player: PlayerId, attack: &Attack) -> Result<AttackOutcome, WolfriskError> {
+        let mut outcome = self.perform_battle(attack)?;
+
+        if let AttackMode::Blitz { stop_at } = attack.mode {
+            loop {
+                let armies = self.board.get_num_armies(attack.origin);
+                if outcome.conquered || armies <= max(stop_at, 1) || self.forfeited.contains(&player) {
+                    break;
+                }
+
+                let amount_attacking = min(attack.amount_attacking, attacking_allowed(armies - 1));
+                let round = self.perform_battle(&Attack::new(attack.origin,
+                                                             attack.target,
+                                                             amount_attacking))?;
+                outcome.rounds += round.rounds;
+                outcome.attacker_losses += round.attacker_losses;
+                outcome.defender_losses += round.defender_losses;
+                outcome.conquered = round.conquered;
+            }
+
+            self.emit(GameEvent::BlitzResolved {
+                player,
+                origin: attack.origin,
+                target: attack.target,
+                rounds: outcome.rounds,
+                attacker_losses: outcome.attacker_losses,
+                defender_losses: outcome.defender_losses,
+                conquered: outcome.conquered,
+            });
+        }
+        Ok(outcome)
+    }
+
+    // fights a single round of `attack`, which has been verified to be valid.
+    // if the target falls, the attacker moves in
+    fn perform_battle(&mut self, attack: &Attack) -> Result<AttackOutcome, WolfriskError> {
         let num_enemy_armies = self.board.get_num_armies(attack.target);
         let amount_defending = defending_allowed(num_enemy_armies);
         let amount_attacking = attack.amount_attacking;
 
-        // we are not rolling any dice here, we are just going to use
-        // a uniform randomly variable and the probability tables
-        let roll = rand::thread_rng().gen_range(0., 1.);
+        let outcome = self.combat.resolve(amount_attacking, amount_defending, &mut self.rng)?;
 
-        let outcome: (NumArmies, NumArmies) =
-            if amount_defending == 1 || amount_attacking == 1 {
-                let dist = one_rolled_1(amount_attacking, amount_defending).unwrap();
-                if roll <= dist[0] {
-                    // attacker loses 1
-                    (1, 0)
-                } else {
-                    // defender loses 1
-                    (0, 1)
-                }
-            } else {
-                let dist = both_rolled_at_least_2(amount_attacking, amount_defending).unwrap();
-                if roll <= dist[0] {
-                    // attacker loses 2
-                    (2, 0)
-                } else if roll > dist[0] && roll <= (dist[0] + dist[1]) {
-                    // defender loses 2
-                    (0, 2)
-                } else {
-                    // both lose 1
-                    (1, 1)
-                }
-            };
+        let must_commit = amount_attacking - outcome.attacker_losses;
+        let player = self.board.get_owner(attack.origin);
+        let defender = self.board.get_owner(attack.target);
 
-        let must_commit = amount_attacking - outcome.0;
+        if outcome.attacker_losses > 0 {
+            self.board.remove_armies(attack.origin, outcome.attacker_losses)?;
+        }
 
-        if outcome.0 > 0 {
-            self.board.remove_armies(attack.origin, outcome.0);
-            println!("Attacking territory {} lost {} units in battle",
-                     attack.origin,
-                     outcome.0);
+        if outcome.defender_losses > 0 {
+            self.board.remove_armies(attack.target, outcome.defender_losses)?;
         }
 
-        if outcome.1 > 0 {
-            self.board.remove_armies(attack.target, outcome.1);
-            println!("Defending territory {} lost {} units in battle",
-                     attack.target,
-                     outcome.1);
+        self.emit(GameEvent::BattleResolved {
+            player,
+            origin: attack.origin,
+            target: attack.target,
+            attacker_losses: outcome.attacker_losses,
+            defender_losses: outcome.defender_losses,
+            attacker_dice: outcome.attacker_dice,
+            defender_dice: outcome.defender_dice,
+        });
+
+        let conquered = self.board.get_num_armies(attack.target) == 0;
+        if conquered {
+            let armies = self.process_combat_move(player, attack.origin, attack.target, must_commit);
+            self.board.remove_armies(attack.origin, armies)?;
+            self.board.set_territory(attack.target, player, armies)?;
+            self.emit(GameEvent::TerritoryConquered {
+                player,
+                previous_owner: defender,
+                origin: attack.origin,
+                target: attack.target,
+                armies,
+            });
+            if self.board.player_is_defeated(defender) {
+                self.emit(GameEvent::PlayerEliminated { player: defender, by: player });
+                self.process_elimination(player, defender)?;
+            }
         }
 
-        if self.board.get_num_armies(attack.target) == 0 {
-            self.board.remove_armies(attack.origin, must_commit);
-            self.board.add_armies(attack.target, must_commit);
-            println!("Territory {} was conquered, moving {} units over from {}",
-                     attack.target,
-                     must_commit,
-                     attack.origin);
-            true
-            // TODO: prompt user for combat move
-        } else {
-            false
+        Ok(AttackOutcome {
+            rounds: 1,
+            attacker_losses: outcome.attacker_losses,
+            defender_losses: outcome.defender_losses,
+            conquered,
+        })
+    }
+
+    // `player` takes the cards of `defender`, whom they just eliminated. if
+    // that leaves them with too many, they have to trade and place the armies
+    // before going on with the attack
+    fn process_elimination(&mut self, player: PlayerId, defender: PlayerId) -> Result<(), WolfriskError> {
+        // the neutral player never holds cards
+        if self.neutral == Some(defender) {
+            return Ok(());
         }
+
+        let cards = self.cards.take_cards(defender, player)?;
+        if !cards.is_empty() {
+            self.emit(GameEvent::CardsCaptured { player, from: defender, cards });
+        }
+
+        // there is no point in trading if that was the last opponent
+        if self.game_is_over() {
+            return Ok(());
+        }
+        if self.cards.get_num_player_cards(player)? >= self.rules.elimination_trade_hand_size {
+            let reinf = self.process_trade(player, true)?;
+            if reinf > 0 && !self.forfeited.contains(&player) {
+                self.distribute_armies(player, reinf)?;
+            }
+        }
+        Ok(())
     }
 
-    pub fn process_fortify(&mut self, player: PlayerId) {
+    // asks `player`, who has just emptied `target`, how many armies to move
+    // in from `origin`. they have to move at least the `min` armies that
+    // survived the battle and have to leave one army behind
+    fn process_combat_move(&mut self,
+                           player: PlayerId,
+                           origin: TerritoryId,
+                           target: TerritoryId,
+                           min: NumArmies)
+                           -> NumArmies {
+        let range = legal::combat_move_range(self.board.as_ref(), origin, min);
+        if range.min >= range.max {
+            return range.min;
+        }
+
+        let mut attempts = 0;
         loop {
+            let armies = self.get_player(player)
+                             .make_combat_move(origin, target, range.min, range.max, self.board.as_ref());
+            match legal::check_combat_move(range, armies) {
+                Ok(()) => return armies,
+                Err(reason) => {
+                    if !self.reject(player, reason, &mut attempts) {
+                        // if the player forfeits, they still hold the
+                        // territory with the armies that survived the battle
+                        self.out_of_retries(player);
+                        return min;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn process_fortify(&mut self, player: PlayerId) -> Result<(), WolfriskError> {
+        let mut moves_left = match self.rules.fortify {
+            FortifyRule::Adjacent | FortifyRule::Connected => 1,
+            FortifyRule::Moves(moves) => moves,
+        };
+
+        let mut attempts = 0;
+        while moves_left > 0 {
             match self.get_player(player).fortify(player, self.board.as_ref()) {
-                None => return,
+                None => return Ok(()),
                 Some(fortify) => {
-                    if self.verify_fortify(player, &fortify) {
-                        self.board.remove_armies(fortify.origin, fortify.amount);
-                        self.board.add_armies(fortify.destination, fortify.amount);
-                        println!("   !!! Player {} moved {} units from {} to {}",
-                                 player,
-                                 fortify.amount,
-                                 fortify.origin,
-                                 fortify.destination);
-                        return;
-                    } else {
-                        println!("Invalid fortify move. Please choose again.");
+                    match self.verify_fortify(player, &fortify) {
+                        Ok(()) => {
+                            attempts = 0;
+                            moves_left -= 1;
+                            self.check_room(&[(fortify.destination, fortify.amount)])?;
+                            self.board.remove_armies(fortify.origin, fortify.amount)?;
+                            self.board.add_armies(fortify.destination, fortify.amount)?;
+                            self.emit(GameEvent::Fortified {
+                                player,
+                                origin: fortify.origin,
+                                destination: fortify.destination,
+                                armies: fortify.amount,
+                            });
+                        }
+                        Err(reason) => {
+                            if !self.reject(player, reason, &mut attempts) {
+                                // the default is not to fortify
+                                self.out_of_retries(player);
+                                return Ok(());
+                            }
+                        }
                     }
                 },
             }
         }
+        Ok(())
     }
 
-    fn verify_trade(&self, player: PlayerId, trade: &Option<Trade>, necessary: bool) -> bool {
+    // tells `player` why their choice was rejected. returns whether they may
+    // choose again, i.e. they haven't used up their retries yet
+    fn reject(&mut self, player: PlayerId, reason: Rejection, attempts: &mut u32) -> bool {
+        self.emit(GameEvent::ActionRejected { player, reason });
+        self.get_player(player).action_rejected(reason);
+        *attempts += 1;
+        *attempts <= self.max_retries
+    }
+
+    // applies the retry policy to a player who has used up their retries.
+    // returns true if the default action should be taken in their place, or
+    // false if they have forfeited
+    fn out_of_retries(&mut self, player: PlayerId) -> bool {
+        match self.retry_policy {
+            RetryPolicy::DefaultAction => true,
+            RetryPolicy::Forfeit => {
+                self.forfeited.insert(player);
+                self.emit(GameEvent::PlayerForfeited { player });
+                false
+            }
+        }
+    }
+
+    fn verify_trade(&self,
+                    player: PlayerId,
+                    trade: &Option<Trade>,
+                    necessary: bool)
+                    -> Result<(), Rejection> {
         match *trade {
-            None => !necessary,
-            Some(ref trade) => self.cards.player_has_3_cards(player, trade.cards) &&
-                               trade.is_set(),
+            None if necessary => Err(Rejection::TradeRequired),
+            None => Ok(()),
+            Some(ref trade) => {
+                let hand = self.cards.get_player_cards(player).unwrap_or_default();
+                legal::check_trade(&hand, trade)
+            }
         }
     }
 
-    fn verify_reinf(&self, player: PlayerId, reinf_amt: NumArmies, reinf: &Reinforcement) -> bool {
-        let mut total_amt = 0;
+    fn verify_reinf(&self,
+                    player: PlayerId,
+                    reinf_amt: NumArmies,
+                    reinf: &Reinforcement)
+                    -> Result<(), Rejection> {
+        let mut total_amt: NumArmies = 0;
         for (&terr, &amt) in reinf.iter() {
-            total_amt += amt;
-            if self.board.is_enemy_territory(player, terr) {
-                return false;
-            }
+            total_amt = total_amt.saturating_add(amt);
+            self.verify_owner(player, terr)?;
         }
-        total_amt == reinf_amt
+        if total_amt != reinf_amt {
+            return Err(Rejection::WrongTotal { expected: reinf_amt, actual: total_amt });
+        }
+        Ok(())
+    }
+
+    fn verify_battle(&self, player: PlayerId, attack: &Attack) -> Result<(), Rejection> {
+        legal::check_attack(self.board.as_ref(),
+                            player,
+                            attack.origin,
+                            attack.target,
+                            attack.amount_attacking)
+    }
+
+    fn verify_fortify(&self, player: PlayerId, fortify: &Move) -> Result<(), Rejection> {
+        legal::check_fortify(self.board.as_ref(), &self.rules, player, fortify)
     }
 
-    fn verify_battle(&self, player: PlayerId, attack: &Attack) -> bool {
-        // if there are that many excess units on the origin territory
-        // and the target territory is actually an adjacent enemy
-        // then the attack is valid. otherwise, not.
-        let can_attack_with = self.board.get_num_armies(attack.origin) - 1;
-        self.board.get_owner(attack.origin) == player &&
-        can_attack_with >= attack.amount_attacking &&
-        self.board.game_map().are_adjacent(attack.origin, attack.target) &&
-        self.board.is_enemy_territory(player, attack.target)
+    // checks that `terr` is on the map and owned by `player`
+    fn verify_owner(&self, player: PlayerId, terr: TerritoryId) -> Result<(), Rejection> {
+        legal::check_owner(self.board.as_ref(), player, terr)
     }
 
-    fn verify_fortify(&self, player: PlayerId, fortify: &Move) -> bool {
-        self.board.get_owner(fortify.origin) == player &&
-        self.board.get_owner(fortify.destination) == player &&
-        self.board.game_map().are_adjacent(fortify.origin, fortify.destination) &&
-        self.board.get_num_armies(fortify.origin) > fortify.amount
+    fn is_territory(&self, terr: TerritoryId) -> bool {
+        (terr as usize) < self.board.game_map().num_territories()
+    }
+
+    // checks that armies can be added to every territory in `additions`
+    // without overflowing, so a change that adds to several territories fails
+    // before any of it is applied rather than half way through
+    fn check_room(&self, additions: &[(TerritoryId, NumArmies)]) -> Result<(), WolfriskError> {
+        let mut totals = BTreeMap::new();
+        for &(terr, armies) in additions {
+            if !self.is_territory(terr) {
+                return Err(WolfriskError::InvalidTerritory(terr));
+            }
+            let total = totals.entry(terr).or_insert_with(|| self.board.get_num_armies(terr));
+            *total = total.checked_add(armies).ok_or(WolfriskError::ArmyOverflow(terr))?;
+        }
+        Ok(())
     }
 
-    fn get_player(&self, id: PlayerId) -> &Player {
+    fn get_player(&self, id: PlayerId) -> &dyn Player {
         self.players[id as usize].as_ref()
     }
 }
 
 
-struct CardManager {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CardManager {
     cards: Vec<Card>,
-    available: HashSet<CardId>,
-    discarded: HashSet<CardId>,
-    player_cards: HashMap<PlayerId, HashSet<CardId>>,
+    available: BTreeSet<CardId>,
+    discarded: BTreeSet<CardId>,
+    player_cards: BTreeMap<PlayerId, BTreeSet<CardId>>,
 }
 
 impl CardManager {
     pub fn new(num_players: usize, cards: Vec<Card>) -> CardManager {
-        let mut map = HashMap::new();
+        let mut map = BTreeMap::new();
         for i in 0..(num_players as PlayerId) {
-            map.insert(i, HashSet::new());
+            map.insert(i, BTreeSet::new());
         }
         let n = cards.len();
         CardManager {
-            cards: cards,
+            cards,
             available: (0..n).collect(),
-            discarded: HashSet::new(),
+            discarded: BTreeSet::new(),
             player_cards: map,
         }
     }
 
-    pub fn standard_card_manager(num_players: usize) -> CardManager {
+    // one card per territory, with the symbols cycling through infantry, cavalry
+    // and artillery, plus two wilds
+    pub fn standard_card_manager<R: Rng>(num_players: usize,
+                                         num_territories: usize,
+                                         rng: &mut R)
+                                         -> CardManager {
         let mut cards = Vec::new();
-        let offset = rand::thread_rng().gen_range(0, 3);
-        for i in 0..42 {
+        let offset = rng.gen_range(0, 3);
+        for i in 0..num_territories {
             cards.push(Card::Territory(i as TerritoryId,
                                        CardSymbol::from_usize((i + offset) % 3).unwrap()))
         }
@@ -434,77 +1287,114 @@ impl CardManager {
         CardManager::new(num_players, cards)
     }
 
-    pub fn player_discard_card(&mut self, player: PlayerId, cid: CardId) {
-        match self.player_cards.get_mut(&player) {
-            None => panic!("Player {} is invalid", player),
-            Some(player_cards) => {
-                if player_cards.remove(&cid) {
-                    self.discarded.insert(cid);
-                }
-            },
+    pub fn num_players(&self) -> usize {
+        self.player_cards.len()
+    }
+
+    pub fn player_discard_card(&mut self, player: PlayerId, cid: CardId) -> Result<(), WolfriskError> {
+        let player_cards = self.player_cards.get_mut(&player)
+                                            .ok_or(WolfriskError::InvalidPlayer(player))?;
+        if !player_cards.remove(&cid) {
+            return Err(WolfriskError::CardNotHeld { player, card: cid });
         }
+        self.discarded.insert(cid);
+        Ok(())
     }
 
     pub fn get_available(&self) -> Vec<CardId> {
-        self.available.iter().map(|&cid| cid).collect()
+        self.available.iter().cloned().collect()
     }
 
-    pub fn get_player_cards(&self, player: PlayerId) -> Vec<CardAndId> {
+    pub fn get_player_cards(&self, player: PlayerId) -> Result<Vec<CardAndId>, WolfriskError> {
         match self.player_cards.get(&player) {
-            None => panic!("Player {} is invalid", player),
-            Some(cards) => cards.iter()
-                                .map(|&id| (self.cards[id], id))
-                                .collect(),
+            None => Err(WolfriskError::InvalidPlayer(player)),
+            Some(cards) => Ok(cards.iter()
+                                   .map(|&id| (self.cards[id], id))
+                                   .collect()),
         }
     }
 
-    pub fn get_num_player_cards(&self, player: PlayerId) -> usize {
+    pub fn get_num_player_cards(&self, player: PlayerId) -> Result<usize, WolfriskError> {
         match self.player_cards.get(&player) {
-            None => panic!("Player {} is invalid", player),
-            Some(cards) => cards.len(),
+            None => Err(WolfriskError::InvalidPlayer(player)),
+            Some(cards) => Ok(cards.len()),
+        }
+    }
+
+    // moves every card held by `from` to `to`, and returns the cards moved
+    pub fn take_cards(&mut self, from: PlayerId, to: PlayerId) -> Result<Vec<Card>, WolfriskError> {
+        if !self.player_cards.contains_key(&to) {
+            return Err(WolfriskError::InvalidPlayer(to));
+        }
+        let taken = match self.player_cards.get_mut(&from) {
+            None => return Err(WolfriskError::InvalidPlayer(from)),
+            Some(cards) => ::std::mem::take(cards),
+        };
+
+        let moved = taken.iter().map(|&cid| self.cards[cid]).collect();
+        if let Some(cards) = self.player_cards.get_mut(&to) {
+            cards.extend(taken);
+        }
+        Ok(moved)
+    }
+
+    // checks that a card manager read from a snapshot can be used with a map
+    // of `num_territories` territories: every card is in exactly one pile and
+    // every territory card names a territory on the map
+    fn check_consistency(&self, num_territories: usize) -> Result<(), SnapshotError> {
+        let mut seen = vec![false; self.cards.len()];
+        let piles = self.player_cards.values().chain(vec![&self.available, &self.discarded]);
+        for pile in piles {
+            for &cid in pile.iter() {
+                let problem = if cid >= self.cards.len() {
+                    format!("there is no card {}", cid)
+                } else if seen[cid] {
+                    format!("card {} is in more than one pile", cid)
+                } else {
+                    seen[cid] = true;
+                    continue;
+                };
+                return Err(SnapshotError::Inconsistent(problem));
+            }
         }
+        for card in self.cards.iter() {
+            if let Card::Territory(terr, _) = *card {
+                if terr as usize >= num_territories {
+                    let problem = format!("card for territory {}, which isn't on the map", terr);
+                    return Err(SnapshotError::Inconsistent(problem));
+                }
+            }
+        }
+        Ok(())
     }
 
     // when the `available` pile is empty, add in the discarded cards.
     fn recycle_discard_pile(&mut self) {
-        self.available.extend(self.discarded.drain());
+        self.available.append(&mut self.discarded);
     }
 
-    pub fn draw_random_for_player(&mut self, player: PlayerId) {
-        if self.available.len() == 0 {
-            self.recycle_discard_pile();
+    // gives `player` a random card from the deck and returns it
+    pub fn draw_random_for_player<R: Rng>(&mut self,
+                                          player: PlayerId,
+                                          rng: &mut R)
+                                          -> Result<Card, WolfriskError> {
+        if !self.player_cards.contains_key(&player) {
+            return Err(WolfriskError::InvalidPlayer(player));
         }
 
-        match self.player_cards.get_mut(&player) {
-            None => panic!("Player {} is invalid", player),
-            Some(cards) => {
-                // clone the card list and shuffle it
-                let mut cids: Vec<_> = self.available.iter().map(|&c| c).collect();
-                rand::thread_rng().shuffle(&mut cids);
-                cards.insert(cids[0]);
-            },
+        if self.available.is_empty() {
+            self.recycle_discard_pile();
         }
-    }
 
-    fn player_has_3_cards(&self, player: PlayerId, cards: [CardAndId; 3]) -> bool {
-        match self.player_cards.get(&player) {
-            None => false,
-            Some(player_cards) => {
-                for i in 0..3 {
-                    // if cards[i].1 is not in player_cards, or if it is
-                    // but self.cards[ cards[i].1 ] doesn't match cards[1].0,
-                    // then return false
-                    let cid = cards[i].1;
-                    let matches: Vec<_> = player_cards.iter()
-                                                      .filter(|&x| *x == cid)
-                                                      .collect();
-                    if matches.len() != 1 || cards[i].0 != self.cards[cid] {
-                        return false;
-                    }
-                }
-                true
-            }
+        // clone the card list and shuffle it
+        let mut cids: Vec<_> = self.available.iter().cloned().collect();
+        rng.shuffle(&mut cids);
+        let cid = *cids.first().ok_or(WolfriskError::NoCardsLeft)?;
+
+        self.available.remove(&cid);
+        if let Some(cards) = self.player_cards.get_mut(&player) {
+            cards.insert(cid);
         }
+        Ok(self.cards[cid])
     }
 }
-
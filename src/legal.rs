@@ -0,0 +1,219 @@
+use std::cmp::max;
+
+use board::GameBoard;
+use error::Rejection;
+use rules::{FortifyRule, Rules};
+use super::{PlayerId, TerritoryId, NumArmies, CardAndId, Trade, Move};
+use super::attacking_allowed;
+
+// What a player can and can't do, worked out from the board the same way
+// for everyone: the engine validates every choice with the `check_*`
+// functions, and the listings are the choices those accept, so a player
+// that picks from a listing is never rejected.
+
+// An attack `player` can make, with 1 to `max_dice` dice.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AttackOption {
+    pub origin: TerritoryId,
+    pub target: TerritoryId,
+    pub max_dice: NumArmies,
+}
+
+// How many armies can move into a territory just conquered: at least the
+// armies that survived the battle, and at most all but one of the armies on
+// the attacking territory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CombatMoveRange {
+    pub min: NumArmies,
+    pub max: NumArmies,
+}
+
+// A fortification `player` can make, moving up to `max_armies` armies.
+// Moving none is allowed, but uses up the move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FortifyOption {
+    pub origin: TerritoryId,
+    pub destination: TerritoryId,
+    pub max_armies: NumArmies,
+}
+
+// every set of three cards in `hand`, in the order the cards are held
+pub fn legal_trades(hand: &[CardAndId]) -> Vec<Trade> {
+    let mut trades = Vec::new();
+    for i in 0..hand.len() {
+        for j in (i + 1)..hand.len() {
+            for k in (j + 1)..hand.len() {
+                let trade = Trade::new([hand[i], hand[j], hand[k]]);
+                if trade.is_set() {
+                    trades.push(trade);
+                }
+            }
+        }
+    }
+    trades
+}
+
+// every attack `player` can make: from each of their territories with more
+// than one army, on each enemy territory next to it
+pub fn attack_options(board: &dyn GameBoard, player: PlayerId) -> Vec<AttackOption> {
+    let mut options = Vec::new();
+    for origin in board.get_owned_territories(player) {
+        let armies = board.get_num_armies(origin);
+        if armies < 2 {
+            continue;
+        }
+        for target in board.game_map().get_neighbors(origin) {
+            if board.is_enemy_territory(player, target) {
+                options.push(AttackOption {
+                    origin,
+                    target,
+                    max_dice: attacking_allowed(armies - 1),
+                });
+            }
+        }
+    }
+    options
+}
+
+// the armies that can move in after an attack from `origin` conquers a
+// territory, with `survivors` of the attacking armies left
+pub fn combat_move_range(board: &dyn GameBoard, origin: TerritoryId, survivors: NumArmies) -> CombatMoveRange {
+    CombatMoveRange {
+        min: survivors,
+        max: max(board.get_num_armies(origin).saturating_sub(1), survivors),
+    }
+}
+
+// every fortification `player` can make under `rules`. territories with a
+// single army are left out, as all they could do is move none
+pub fn fortify_options(board: &dyn GameBoard, rules: &Rules, player: PlayerId) -> Vec<FortifyOption> {
+    let mut options = Vec::new();
+    for origin in board.get_owned_territories(player) {
+        let armies = board.get_num_armies(origin);
+        if armies < 2 {
+            continue;
+        }
+        for destination in fortify_destinations(board, rules, player, origin) {
+            options.push(FortifyOption {
+                origin,
+                destination,
+                max_armies: armies - 1,
+            });
+        }
+    }
+    options
+}
+
+// the territories `player` can fortify from `origin` under `rules`
+pub fn fortify_destinations(board: &dyn GameBoard,
+                            rules: &Rules,
+                            player: PlayerId,
+                            origin: TerritoryId)
+                            -> Vec<TerritoryId> {
+    match rules.fortify {
+        FortifyRule::Adjacent | FortifyRule::Moves(_) => {
+            board.game_map()
+                 .get_neighbors(origin)
+                 .into_iter()
+                 .filter(|&terr| board.get_owner(terr) == player)
+                 .collect()
+        }
+        FortifyRule::Connected => {
+            board.connected_territories(player, origin)
+                 .into_iter()
+                 .filter(|&terr| terr != origin)
+                 .collect()
+        }
+    }
+}
+
+// checks that `trade` is a set made of different cards from `hand`
+pub fn check_trade(hand: &[CardAndId], trade: &Trade) -> Result<(), Rejection> {
+    for (i, &(card, cid)) in trade.cards.iter().enumerate() {
+        if trade.cards[..i].iter().any(|&(_, other)| other == cid) {
+            return Err(Rejection::DuplicateCard(cid));
+        }
+        if !hand.contains(&(card, cid)) {
+            return Err(Rejection::CardNotHeld(cid));
+        }
+    }
+    if !trade.is_set() {
+        return Err(Rejection::NotASet);
+    }
+    Ok(())
+}
+
+// checks that `player` can attack `target` from `origin` with `dice` dice
+pub fn check_attack(board: &dyn GameBoard,
+                    player: PlayerId,
+                    origin: TerritoryId,
+                    target: TerritoryId,
+                    dice: NumArmies)
+                    -> Result<(), Rejection> {
+    check_owner(board, player, origin)?;
+    if !is_territory(board, target) {
+        return Err(Rejection::InvalidTerritory(target));
+    }
+    if !board.is_enemy_territory(player, target) {
+        return Err(Rejection::NotAnEnemy(target));
+    }
+    if !board.game_map().are_adjacent(origin, target) {
+        return Err(Rejection::NotAdjacent { from: origin, to: target });
+    }
+    if dice == 0 {
+        return Err(Rejection::NoArmies);
+    }
+    let can_attack_with = attacking_allowed(board.get_num_armies(origin).saturating_sub(1));
+    if dice > can_attack_with {
+        return Err(Rejection::TooManyArmies { allowed: can_attack_with, requested: dice });
+    }
+    Ok(())
+}
+
+pub fn check_combat_move(range: CombatMoveRange, armies: NumArmies) -> Result<(), Rejection> {
+    if armies < range.min {
+        Err(Rejection::TooFewArmies { required: range.min, requested: armies })
+    } else if armies > range.max {
+        Err(Rejection::TooManyArmies { allowed: range.max, requested: armies })
+    } else {
+        Ok(())
+    }
+}
+
+// checks that `player` can make `fortify` under `rules`
+pub fn check_fortify(board: &dyn GameBoard,
+                     rules: &Rules,
+                     player: PlayerId,
+                     fortify: &Move)
+                     -> Result<(), Rejection> {
+    check_owner(board, player, fortify.origin)?;
+    check_owner(board, player, fortify.destination)?;
+    if !fortify_destinations(board, rules, player, fortify.origin).contains(&fortify.destination) {
+        return Err(match rules.fortify {
+            FortifyRule::Adjacent | FortifyRule::Moves(_) => {
+                Rejection::NotAdjacent { from: fortify.origin, to: fortify.destination }
+            }
+            FortifyRule::Connected => Rejection::NotConnected { from: fortify.origin, to: fortify.destination },
+        });
+    }
+    let can_move = board.get_num_armies(fortify.origin).saturating_sub(1);
+    if fortify.amount > can_move {
+        return Err(Rejection::TooManyArmies { allowed: can_move, requested: fortify.amount });
+    }
+    Ok(())
+}
+
+// checks that `terr` is on the map and owned by `player`
+pub fn check_owner(board: &dyn GameBoard, player: PlayerId, terr: TerritoryId) -> Result<(), Rejection> {
+    if !is_territory(board, terr) {
+        Err(Rejection::InvalidTerritory(terr))
+    } else if board.get_owner(terr) != player {
+        Err(Rejection::NotOwner(terr))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_territory(board: &dyn GameBoard, terr: TerritoryId) -> bool {
+    (terr as usize) < board.game_map().num_territories()
+}
@@ -0,0 +1,263 @@
+extern crate bincode;
+extern crate petgraph;
+extern crate rand;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use rand::SeedableRng;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub use board::{GameBoard, GameMap, StandardGameBoard, UNCLAIMED};
+pub use combat::{BattleResult, CombatResolver, DiceResolver, TableResolver};
+pub use error::{Rejection, WolfriskError};
+pub use eval::{EvalWeights, Evaluator, WeightedEvaluator};
+pub use event::GameEvent;
+pub use game_manager::{GameManager, GameManagerBuilder, CardManager, RetryPolicy, SetupMode};
+pub use legal::{AttackOption, CombatMoveRange, FortifyOption};
+pub use legal::{attack_options, combat_move_range, fortify_options, legal_trades};
+pub use map::{Continent, MapDefinition, MapError, WorldMap, standard_map};
+pub use observer::{ConsoleObserver, GameObserver, Phase};
+pub use odds::{BattleOdds, ChainOdds, OddsCalculator};
+pub use player::Player;
+pub use replay::{Replay, ReplayError};
+pub use rules::{CardValuation, FortifyRule, Rules};
+pub use snapshot::{GameSnapshot, SnapshotError, SnapshotFormat};
+pub use state::{Action, GameState, TurnStage};
+pub use validate::{MapProblem, validate_map};
+
+pub mod board;
+pub mod combat;
+pub mod error;
+pub mod eval;
+pub mod event;
+pub mod game_manager;
+pub mod legal;
+pub mod map;
+pub mod observer;
+pub mod odds;
+pub mod player;
+pub mod replay;
+pub mod rules;
+pub mod snapshot;
+pub mod state;
+pub mod validate;
+
+pub type TerritoryId = u8;
+pub type PlayerId = u8;
+pub type NumArmies = u16;
+pub type CardId = usize;
+pub type CardAndId = (Card, CardId);
+pub type AttackTerritories = BTreeMap<TerritoryId, AttackTerritoryInfo>;
+
+// The random number generator used by the engine and the computer players.
+// Two games started from the same seed, where the players make the same
+// decisions, play out identically.
+pub type GameRng = rand::XorShiftRng;
+
+// makes a `GameRng` from a 64-bit seed
+pub fn seeded_rng(seed: u64) -> GameRng {
+    // the xorshift state must not be all zeros, so mix the seed into the
+    // generator's default state rather than using it directly
+    let lo = seed as u32;
+    let hi = (seed >> 32) as u32;
+    GameRng::from_seed([lo ^ 0x193a_6754, hi ^ 0xa8a7_d469, lo ^ 0x9783_0e05, hi ^ 0x113b_a7bb])
+}
+
+
+pub struct Trade {
+    pub cards: [CardAndId; 3],
+}
+
+impl Trade {
+    pub fn new(cards: [CardAndId; 3]) -> Trade {
+        Trade { cards }
+    }
+
+    fn cards_as_tuple(&self) -> (Card, Card, Card) {
+        (self.cards[0].0, self.cards[1].0, self.cards[2].0)
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.contains_wild() || self.is_non_wild_set()
+    }
+
+    fn contains_wild(&self) -> bool {
+        self.num_wild() > 0
+    }
+
+    fn num_wild(&self) -> usize {
+        let mut count = 0;
+        for i in 0..3 {
+            if self.cards[i].0.is_wild() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // returns whether the 3 cards contain no wilds but still form a set
+    // (i.e. 3 of a kind or 1 of each kind)
+    fn is_non_wild_set(&self) -> bool {
+        match self.cards_as_tuple() {
+            (Card::Territory(_, symbol0),
+             Card::Territory(_, symbol1),
+             Card::Territory(_, symbol2)) => {
+                (symbol0 == symbol1 && symbol1 == symbol2) ||
+                (symbol0 != symbol1 && symbol1 != symbol2 && symbol0 != symbol2)
+            }
+            _ => false,
+        }
+    }
+}
+
+
+pub struct Reinforcement {
+    reinf: BTreeMap<TerritoryId, NumArmies>,
+}
+
+impl Reinforcement {
+    pub fn new(reinf: BTreeMap<TerritoryId, NumArmies>) -> Reinforcement {
+        Reinforcement { reinf }
+    }
+
+    pub fn iter(&self) -> std::collections::btree_map::Iter<'_, TerritoryId, NumArmies> {
+        self.reinf.iter()
+    }
+}
+
+
+// How long an attack goes on for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttackMode {
+    // a single round of dice
+    Single,
+    // round after round, until the target falls or the attacking territory
+    // is down to `stop_at` armies. the first round is always fought
+    Blitz { stop_at: NumArmies },
+}
+
+pub struct Attack {
+    pub origin: TerritoryId,
+    pub target: TerritoryId,
+    // the armies attacking in each round. in a blitz this drops as the
+    // attacking territory runs low
+    pub amount_attacking: NumArmies,
+    pub mode: AttackMode,
+}
+
+impl Attack {
+    pub fn new(origin: TerritoryId,
+           target: TerritoryId,
+           amount_attacking: NumArmies)
+           -> Attack {
+        Attack {
+            origin,
+            target,
+            amount_attacking,
+            mode: AttackMode::Single,
+        }
+    }
+
+    pub fn blitz(origin: TerritoryId,
+                 target: TerritoryId,
+                 amount_attacking: NumArmies,
+                 stop_at: NumArmies)
+                 -> Attack {
+        Attack {
+            origin,
+            target,
+            amount_attacking,
+            mode: AttackMode::Blitz { stop_at },
+        }
+    }
+}
+
+// What an attack came to, over all of its rounds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttackOutcome {
+    pub rounds: usize,
+    pub attacker_losses: NumArmies,
+    pub defender_losses: NumArmies,
+    pub conquered: bool,
+}
+
+
+pub struct Move {
+    pub origin: TerritoryId,
+    pub destination: TerritoryId,
+    pub amount: NumArmies,
+}
+
+
+pub struct AttackTerritoryInfo {
+    pub id: TerritoryId,
+    pub armies: NumArmies,
+    pub adj_enemies: BTreeSet<TerritoryId>,
+}
+
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CardSymbol {
+    Infantry,
+    Cavalry,
+    Artillery,
+}
+
+impl CardSymbol {
+    fn from_usize(x: usize) -> Option<CardSymbol> {
+        match x {
+            0 => Some(CardSymbol::Infantry),
+            1 => Some(CardSymbol::Cavalry),
+            2 => Some(CardSymbol::Artillery),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Card {
+    Territory(TerritoryId, CardSymbol),
+    Wild,
+}
+
+impl Card {
+    pub fn is_wild(&self) -> bool {
+        matches!(*self, Card::Wild)
+    }
+
+    pub fn get_symbol(&self) -> Option<CardSymbol> {
+        match *self {
+            Card::Territory(_, sym) => Some(sym),
+            Card::Wild => None,
+        }
+    }
+
+    pub fn get_territory(&self) -> Option<TerritoryId> {
+        match *self {
+            Card::Territory(terr, _) => Some(terr),
+            Card::Wild => None,
+        }
+    }
+
+}
+
+
+pub fn attacking_allowed(pool: NumArmies) -> NumArmies {
+    max_allowed(3, pool)
+}
+
+pub fn defending_allowed(pool: NumArmies) -> NumArmies {
+    max_allowed(2, pool)
+}
+
+// given `max` and `pool`, returns min(`max`, `pool`)
+fn max_allowed(max: NumArmies, pool: NumArmies) -> NumArmies {
+    if pool > max {
+        max
+    } else {
+        pool
+    }
+}
@@ -1,255 +1,297 @@
-extern crate petgraph;
 extern crate rand;
+extern crate wolfrisk;
+
+use rand::Rng;
+use std::env;
+use std::io;
+use std::process;
+
+use wolfrisk::{ConsoleObserver, GameManager, GameManagerBuilder, GameMap, GameSnapshot, SnapshotFormat, WorldMap};
+use wolfrisk::{DiceResolver, GameBoard, NumArmies, OddsCalculator, Replay, Rules, SetupMode};
+use wolfrisk::{WolfriskError, seeded_rng};
+use wolfrisk::event;
+use wolfrisk::player::{HeuristicPlayer, HumanPlayer, MctsPlayer, RandomPlayer, SearchBudget};
+
+// usage:
+//    wolfrisk [--seed <n>] [--map <file>] [--rules <preset>] [--draft] [--dice] [--heuristic]
+//             [--mcts] [--load <file>] [--save <file>] [--log <file>] [--quiet]
+//        play a game. --rules picks classic (the default), unlimited, progressive,
+//        connected-fortify or no-continent-bonuses rules, --draft lets the players claim the
+//        territories in turn instead of dealing them out at random, --dice rolls dice for
+//        every battle instead of using the odds tables, --heuristic plays against
+//        heuristic computer players instead of random ones, --mcts against players that
+//        search ahead with Monte Carlo tree search, --load resumes a saved game with
+//        the rules it was saved with, --save saves the game after every turn (as JSON
+//        if the file name ends in .json, binary otherwise), --log writes the game's
+//        events to a file once the game is over, --quiet turns off the running
+//        commentary
+//    wolfrisk validate-map <file>
+//        check a map file for problems
+//    wolfrisk replay <file> [--step]
+//        rebuild a game from the events written with --log. --step waits for
+//        enter after every event
+//    wolfrisk odds <attackers> <defenders> [<defenders>...]
+//        the odds of attacking until one side runs out of armies. <attackers>
+//        doesn't count the army left behind. with more than one <defenders>,
+//        the odds of pushing through each territory in turn
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-use std::collections::{HashMap, HashSet};
-
-pub use board::{GameBoard, GameMap};
-use player::{RandomPlayer, HumanPlayer};
-use game_manager::GameManager;
-
-mod board;
-mod game_manager;
-mod player;
-
-pub const NUM_TERRITORIES: usize = 42;
-
-pub type TerritoryId = u8;
-pub type PlayerId = u8;
-pub type NumArmies = u16;
-pub type CardId = usize;
-pub type CardAndId = (Card, CardId);
-pub type AttackTerritories = HashMap<TerritoryId, AttackTerritoryInfo>;
-
-
-pub struct Trade {
-    pub cards: [CardAndId; 3],
-}
-
-impl Trade {
-    fn new(cards: [CardAndId; 3]) -> Trade {
-        Trade { cards: cards }
-    }
-
-    fn cards_as_tuple(&self) -> (Card, Card, Card) {
-        (self.cards[0].0, self.cards[1].0, self.cards[2].0)
+    match args.first().map(|s| s.as_str()) {
+        Some("validate-map") => validate_map(&args[1..]),
+        Some("replay") => replay(&args[1..]),
+        Some("odds") => odds(&args[1..]),
+        _ => play(&args),
     }
+}
 
-    fn is_set(&self) -> bool {
-        self.contains_wild() || self.is_non_wild_set()
-    }
+// returns the argument following `flag`, if `flag` was given
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
 
-    fn contains_wild(&self) -> bool {
-        self.num_wild() > 0
+fn load_map_or_exit(path: &str) -> WorldMap {
+    match WorldMap::load(path) {
+        Ok(map) => map,
+        Err(err) => {
+            println!("Error loading map {}: {}", path, err);
+            process::exit(1);
+        }
     }
+}
 
-    fn num_wild(&self) -> usize {
-        let mut count = 0;
-        for i in 0..3 {
-            if self.cards[i].0.is_wild() {
-                count += 1;
-            }
+fn play(args: &[String]) {
+    let seed = match option_value(args, "--seed").map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            println!("--seed expects an unsigned integer, ignoring it");
+            rand::thread_rng().gen()
         }
-        count
-    }
-
-    // returns whether the 3 cards contain no wilds but still form a set
-    // (i.e. 3 of a kind or 1 of each kind)
-    fn is_non_wild_set(&self) -> bool {
-        match self.cards_as_tuple() {
-            (Card::Territory(_, symbol0),
-             Card::Territory(_, symbol1),
-             Card::Territory(_, symbol2)) => {
-                if symbol0 == symbol1 && symbol1 == symbol2 {
-                    true
-                } else if symbol0 != symbol1 && symbol1 != symbol2 && symbol0 != symbol2 {
-                    true
-                } else {
-                    false
-                }
+        None => rand::thread_rng().gen(),
+    };
+    println!("Hello, world! (seed {})", seed);
+
+    let snapshot = option_value(args, "--load").map(|path| {
+        match GameSnapshot::load(path) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                println!("Error loading game {}: {}", path, err);
+                process::exit(1);
             }
-            _ => false,
-        }
-    }
-
-    // TODO: this should probably be in a Rules object
-    // or something
-    fn value(&self) -> NumArmies {
-        if !self.is_set() {
-            0
-        } else {
-            match self.cards_as_tuple() {
-                (Card::Territory(_, sym0),
-                 Card::Territory(_, sym1),
-                 Card::Territory(_, sym2)) => {
-                    if sym0 == sym1 && sym1 == sym2 {
-                        Trade::value_for_uniform_set(sym0)
-                    } else if sym0 != sym1 && sym1 != sym2 && sym0 != sym2 {
-                        10
-                    } else {
-                        0
-                    }
+        }
+    });
+    let num_players = snapshot.as_ref().map_or(4, |s| s.num_players as usize);
+
+    // a resumed game keeps the rules it was saved with
+    let rules = match (option_value(args, "--rules"), snapshot.as_ref()) {
+        (None, None) => Rules::default(),
+        (None, Some(snapshot)) => snapshot.rules.clone(),
+        (Some(_), Some(snapshot)) => {
+            println!("--rules is ignored when resuming a game");
+            snapshot.rules.clone()
+        }
+        (Some(name), None) => {
+            match Rules::preset(name) {
+                Some(rules) => rules,
+                None => {
+                    println!("Unknown rules {}", name);
+                    process::exit(2);
                 }
-                cards => {
-                    // trade contains a wild
-                    if self.num_wild() == 2 {
-                        10
-                    } else {
-                        let cards = vec![cards.0, cards.1, cards.2];
-
-                        let i = if cards[0].is_wild() {
-                            0
-                        } else if cards[1].is_wild() {
-                            1
-                        } else {
-                            2
-                        };
-
-                        if cards[(i + 1) % 3] == cards[(i + 2) % 3] {
-                            let sym = cards[(i + 1) % 3].get_symbol()
-                                                        .expect("There seems to be more than one wild, which .num_wild() did not detect.");
-                            Trade::value_for_uniform_set(sym)
-                        } else {
-                            10
-                        }
-                    }
-                },
             }
         }
+    };
+
+    let mut rng = seeded_rng(seed);
+    let opponents = if args.iter().any(|arg| arg == "--heuristic") {
+        HeuristicPlayer::make_heuristic_players(num_players - 1)
+    } else if args.iter().any(|arg| arg == "--mcts") {
+        MctsPlayer::make_mcts_players(num_players - 1, SearchBudget::default(), &rules, &mut rng)
+    } else {
+        RandomPlayer::make_random_players(num_players - 1, &mut rng)
+    };
+    let mut builder = GameManagerBuilder::new()
+                          .players(opponents)
+                          .player(Box::new(HumanPlayer))
+                          .rules(rules);
+    if args.iter().any(|arg| arg == "--draft") {
+        builder = builder.setup_mode(SetupMode::Draft);
+    }
+    if args.iter().any(|arg| arg == "--dice") {
+        builder = builder.combat_resolver(Box::new(DiceResolver));
+    }
+    if !args.iter().any(|arg| arg == "--quiet") {
+        builder = builder.observer(Box::new(ConsoleObserver));
     }
 
+    if let Some(snapshot) = snapshot {
+        builder = match builder.restore(snapshot) {
+            Ok(builder) => builder,
+            Err(err) => {
+                println!("Error restoring game: {}", err);
+                process::exit(1);
+            }
+        };
+    } else if let Some(path) = option_value(args, "--map") {
+        builder = builder.map(load_map_or_exit(path));
+    }
 
-    // value for a set where all cards have the given CardSymbol
-    fn value_for_uniform_set(x: CardSymbol) -> NumArmies {
-        match x {
-            CardSymbol::Infantry => 4,
-            CardSymbol::Cavalry => 6,
-            CardSymbol::Artillery => 8,
+    let mut mgr = match builder.rng(rng).build() {
+        Ok(mgr) => mgr,
+        Err(err) => {
+            println!("Error setting up the game: {}", err);
+            process::exit(1);
         }
+    };
+    let result = match option_value(args, "--save") {
+        None => mgr.run(),
+        Some(path) => save_every_turn(&mut mgr, path),
+    };
+    if let Err(err) = result {
+        println!("The game was aborted: {}", err);
     }
-}
-
-
-pub struct Reinforcement {
-    reinf: HashMap<TerritoryId, NumArmies>,
-}
 
-impl Reinforcement {
-    fn new(reinf: HashMap<TerritoryId, NumArmies>) -> Reinforcement {
-        Reinforcement { reinf: reinf }
+    if let Some(path) = option_value(args, "--log") {
+        if let Err(err) = event::save_events(path, mgr.events()) {
+            println!("Warning: couldn't write event log to {}: {}", path, err);
+        }
     }
+}
 
-    fn iter(&self) -> std::collections::hash_map::Iter<TerritoryId, NumArmies> {
-        self.reinf.iter()
+fn save_every_turn(mgr: &mut GameManager, path: &str) -> Result<(), WolfriskError> {
+    while mgr.play_turn()? {
+        save_or_warn(mgr, path);
     }
+    save_or_warn(mgr, path);
+    Ok(())
 }
 
-
-pub struct Attack {
-    pub origin: TerritoryId,
-    pub target: TerritoryId,
-    pub amount_attacking: NumArmies,
+fn save_or_warn(mgr: &GameManager, path: &str) {
+    if let Err(err) = mgr.snapshot().save(path, SnapshotFormat::for_path(path)) {
+        println!("Warning: couldn't save game to {}: {}", path, err);
+    }
 }
 
-impl Attack {
-    fn new(origin: TerritoryId,
-           target: TerritoryId,
-           amount_attacking: NumArmies)
-           -> Attack {
-        Attack {
-            origin: origin,
-            target: target,
-            amount_attacking: amount_attacking,
+fn validate_map(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("usage: wolfrisk validate-map <file>");
+            process::exit(2);
         }
+    };
+
+    let map = load_map_or_exit(path);
+    let problems = map.validate();
+    if problems.is_empty() {
+        println!("{}: map \"{}\" is valid ({} territories, {} continents)",
+                 path,
+                 map.name(),
+                 map.num_territories(),
+                 map.continents().len());
+        return;
     }
-}
-
-
-pub struct Move {
-    pub origin: TerritoryId,
-    pub destination: TerritoryId,
-    pub amount: NumArmies,
-}
 
-
-pub struct AttackTerritoryInfo {
-    pub id: TerritoryId,
-    pub armies: NumArmies,
-    pub adj_enemies: HashSet<TerritoryId>,
+    let name = |t| format!("{} ({})", map.territory_name(t), t);
+    let continent = |c: usize| format!("continent {} ({})", map.continents()[c].name, c);
+    for problem in problems.iter() {
+        println!("{}: {}", path, problem.describe(&name, &continent));
+    }
+    println!("{} problem(s) found", problems.len());
+    process::exit(1);
 }
 
+fn replay(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("usage: wolfrisk replay <file> [--step]");
+            process::exit(2);
+        }
+    };
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum CardSymbol {
-    Infantry,
-    Cavalry,
-    Artillery,
-}
-
-impl CardSymbol {
-    fn from_usize(x: usize) -> Option<CardSymbol> {
-        match x {
-            0 => Some(CardSymbol::Infantry),
-            1 => Some(CardSymbol::Cavalry),
-            2 => Some(CardSymbol::Artillery),
-            _ => None,
+    let events = match event::load_events(path) {
+        Ok(events) => events,
+        Err(err) => {
+            println!("Error reading event log {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+    let mut replay = match Replay::new(events) {
+        Ok(replay) => replay,
+        Err(err) => {
+            println!("Error replaying {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let step = args.iter().any(|arg| arg == "--step");
+    loop {
+        match replay.step() {
+            Ok(Some(event)) => {
+                println!("{:?}", event);
+                if step {
+                    let mut line = String::new();
+                    let _ = io::stdin().read_line(&mut line);
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                println!("Error replaying {}: {}", path, err);
+                process::exit(1);
+            }
         }
     }
-}
-
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum Card {
-    Territory(TerritoryId, CardSymbol),
-    Wild,
+    let board = replay.board();
+    println!("Final board:");
+    for terr in 0..board.game_map().num_territories() {
+        let terr = terr as u8;
+        println!("  {}: player {} with {} armies",
+                 board.game_map().territory_name(terr),
+                 board.get_owner(terr),
+                 board.get_num_armies(terr));
+    }
 }
 
-impl Card {
-    fn is_wild(&self) -> bool {
-        if let &Card::Wild = self {
-            true
-        } else {
-            false
+fn odds(args: &[String]) {
+    let armies: Result<Vec<NumArmies>, _> = args.iter().map(|arg| arg.parse()).collect();
+    let armies = match armies {
+        Ok(ref armies) if armies.len() >= 2 => armies.clone(),
+        _ => {
+            println!("usage: wolfrisk odds <attackers> <defenders> [<defenders>...]");
+            process::exit(2);
         }
-    }
-
-    fn get_symbol(&self) -> Option<CardSymbol> {
-        match *self {
-            Card::Territory(_, sym) => Some(sym),
-            Card::Wild => None,
+    };
+    let attackers = armies[0];
+    let defenders = &armies[1..];
+
+    let calc = OddsCalculator::new();
+    let battle = calc.battle(attackers, defenders[0]);
+    println!("{} attacking {}:", attackers, defenders[0]);
+    println!("  attacker wins: {:.2}%", battle.attacker_wins() * 100.);
+    println!("  expected losses: attacker {:.2}, defender {:.2}",
+             battle.expected_attacker_losses(),
+             battle.expected_defender_losses());
+    for (left, &p) in battle.attacker_left.iter().enumerate().rev() {
+        if p > 0. {
+            println!("  {:.2}%: attacker wins with {} left", p * 100., left);
         }
     }
-
-    fn get_territory(&self) -> Option<TerritoryId> {
-        match *self {
-            Card::Territory(terr, _) => Some(terr),
-            Card::Wild => None,
+    for (left, &p) in battle.defender_left.iter().enumerate() {
+        if p > 0. {
+            println!("  {:.2}%: defender holds with {} left", p * 100., left);
         }
     }
 
-}
-
-
-fn attacking_allowed(pool: NumArmies) -> NumArmies {
-    max_allowed(3, pool)
-}
-
-fn defending_allowed(pool: NumArmies) -> NumArmies {
-    max_allowed(2, pool)
-}
-
-// given `max` and `pool`, returns min(`max`, `pool`)
-fn max_allowed(max: NumArmies, pool: NumArmies) -> NumArmies {
-    if pool > max {
-        max
-    } else {
-        pool
+    if defenders.len() > 1 {
+        let chain = calc.chain(attackers, defenders);
+        println!("{} attacking {:?} in turn:", attackers, defenders);
+        for (i, &p) in chain.captured.iter().enumerate() {
+            println!("  {:.2}%: takes territory {} ({} defending)", p * 100., i + 1, defenders[i]);
+        }
+        println!("  expected attacker losses: {:.2}", chain.expected_attacker_losses);
     }
 }
-
-fn main() {
-    println!("Hello, world!");
-    let mut players = RandomPlayer::make_random_players(3);
-    players.push(Box::new(HumanPlayer));
-    let mut mgr = GameManager::new_game(players);
-    mgr.run();
-}
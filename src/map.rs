@@ -0,0 +1,270 @@
+use petgraph::graph::NodeIndex;
+use serde_json;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use board::{GameMap, TerritoryGraph};
+use validate::{MapProblem, validate_map};
+use super::TerritoryId;
+
+// A map file is a JSON document of the form
+//
+//    {
+//        "name": "Classic",
+//        "territories": [
+//            { "name": "Congo", "neighbors": ["East Africa", "North Africa", "South Africa"] },
+//            ...
+//        ],
+//        "continents": [
+//            { "name": "Africa", "bonus": 3, "territories": ["Congo", ...] },
+//            ...
+//        ]
+//    }
+//
+// Territories are given IDs in the order they are declared. Neighbors and
+// continent members refer to territories by name. Adjacency is kept exactly as
+// declared, so every border should be listed from both sides.
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapDefinition {
+    pub name: String,
+    pub territories: Vec<TerritoryDefinition>,
+    pub continents: Vec<ContinentDefinition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerritoryDefinition {
+    pub name: String,
+    pub neighbors: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContinentDefinition {
+    pub name: String,
+    pub bonus: u8,
+    pub territories: Vec<String>,
+}
+
+impl MapDefinition {
+    pub fn from_json(json: &str) -> Result<MapDefinition, MapError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<MapDefinition, MapError> {
+        let mut json = String::new();
+        File::open(path)?.read_to_string(&mut json)?;
+        MapDefinition::from_json(&json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("A MapDefinition can always be serialized")
+    }
+
+    // describes any GameMap and continent table as a definition, so that it
+    // can be written to a file and loaded back as a `WorldMap`
+    pub fn from_map(map: &dyn GameMap, continents: &[Continent]) -> MapDefinition {
+        let name_of = |tid: &TerritoryId| map.territory_name(*tid);
+        MapDefinition {
+            name: map.name(),
+            territories: (0..map.num_territories())
+                             .map(|i| {
+                                 TerritoryDefinition {
+                                     name: map.territory_name(i as TerritoryId),
+                                     neighbors: map.get_neighbors(i as TerritoryId)
+                                                   .iter()
+                                                   .map(&name_of)
+                                                   .collect(),
+                                 }
+                             })
+                             .collect(),
+            continents: continents.iter()
+                                  .map(|cont| {
+                                      ContinentDefinition {
+                                          name: cont.name.clone(),
+                                          bonus: cont.bonus,
+                                          territories: cont.territories
+                                                           .iter()
+                                                           .map(&name_of)
+                                                           .collect(),
+                                      }
+                                  })
+                                  .collect(),
+        }
+    }
+}
+
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    NoTerritories,
+    // territory IDs and territory counts are `u8`s, so a map can't have more
+    // than 255 territories
+    TooManyTerritories(usize),
+    DuplicateTerritory(String),
+    // a neighbor or continent member names a territory that isn't declared
+    UnknownTerritory(String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MapError::Io(ref err) => write!(f, "couldn't read map file: {}", err),
+            MapError::Parse(ref err) => write!(f, "couldn't parse map file: {}", err),
+            MapError::NoTerritories => write!(f, "map has no territories"),
+            MapError::TooManyTerritories(n) => {
+                write!(f, "map has {} territories, at most {} are supported", n, TerritoryId::MAX)
+            }
+            MapError::DuplicateTerritory(ref name) => {
+                write!(f, "territory \"{}\" is declared more than once", name)
+            }
+            MapError::UnknownTerritory(ref name) => {
+                write!(f, "territory \"{}\" is referenced but never declared", name)
+            }
+        }
+    }
+}
+
+impl Error for MapError {}
+
+impl From<io::Error> for MapError {
+    fn from(err: io::Error) -> MapError {
+        MapError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(err: serde_json::Error) -> MapError {
+        MapError::Parse(err)
+    }
+}
+
+
+// a group of territories that grants a bonus to the player holding all of them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Continent {
+    pub name: String,
+    pub bonus: u8,
+    pub territories: Vec<TerritoryId>,
+}
+
+// A GameMap built from a `MapDefinition`, which also knows the names of its
+// territories and its continents.
+#[derive(Clone)]
+pub struct WorldMap {
+    name: String,
+    territory_names: Vec<String>,
+    graph: TerritoryGraph,
+    continents: Vec<Continent>,
+}
+
+impl WorldMap {
+    pub fn from_definition(def: &MapDefinition) -> Result<WorldMap, MapError> {
+        let n = def.territories.len();
+        if n == 0 {
+            return Err(MapError::NoTerritories);
+        } else if n > TerritoryId::MAX as usize {
+            return Err(MapError::TooManyTerritories(n));
+        }
+
+        let mut ids = HashMap::new();
+        for (i, terr) in def.territories.iter().enumerate() {
+            if ids.insert(terr.name.as_str(), i as TerritoryId).is_some() {
+                return Err(MapError::DuplicateTerritory(terr.name.clone()));
+            }
+        }
+        let lookup = |name: &String| -> Result<TerritoryId, MapError> {
+            ids.get(name.as_str()).cloned().ok_or_else(|| MapError::UnknownTerritory(name.clone()))
+        };
+
+        let mut graph = TerritoryGraph::with_capacity(n, 0);
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        for (i, terr) in def.territories.iter().enumerate() {
+            for neighbor in terr.neighbors.iter() {
+                let j = lookup(neighbor)?;
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j as usize), ());
+            }
+        }
+
+        let mut continents = Vec::new();
+        for cont in def.continents.iter() {
+            let mut territories = Vec::new();
+            for name in cont.territories.iter() {
+                territories.push(lookup(name)?);
+            }
+            continents.push(Continent {
+                name: cont.name.clone(),
+                bonus: cont.bonus,
+                territories,
+            });
+        }
+
+        Ok(WorldMap {
+            name: def.name.clone(),
+            territory_names: def.territories.iter().map(|t| t.name.clone()).collect(),
+            graph,
+            continents,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<WorldMap, MapError> {
+        WorldMap::from_definition(&MapDefinition::load(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<WorldMap, MapError> {
+        WorldMap::from_definition(&MapDefinition::from_json(json)?)
+    }
+
+    // converts back into the definition this map could have been loaded from
+    pub fn to_definition(&self) -> MapDefinition {
+        MapDefinition::from_map(self, &self.continents)
+    }
+
+    pub fn territory_id(&self, name: &str) -> Option<TerritoryId> {
+        self.territory_names.iter().position(|n| n == name).map(|i| i as TerritoryId)
+    }
+
+    pub fn continents(&self) -> &[Continent] {
+        &self.continents
+    }
+
+    // checks this map and its continents with `validate_map`
+    pub fn validate(&self) -> Vec<MapProblem> {
+        validate_map(self, &self.continents)
+    }
+}
+
+impl GameMap for WorldMap {
+    fn are_adjacent(&self, a: TerritoryId, b: TerritoryId) -> bool {
+        self.graph.are_adjacent(a, b)
+    }
+
+    fn get_neighbors(&self, terr: TerritoryId) -> Vec<TerritoryId> {
+        self.graph.get_neighbors(terr)
+    }
+
+    fn num_territories(&self) -> usize {
+        self.graph.num_territories()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn territory_name(&self, terr: TerritoryId) -> String {
+        self.territory_names[terr as usize].clone()
+    }
+}
+
+// the classic 42 territory world map
+pub fn standard_map() -> WorldMap {
+    WorldMap::from_json(include_str!("../maps/standard.json"))
+        .expect("The bundled standard map is invalid")
+}
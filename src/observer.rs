@@ -0,0 +1,189 @@
+use board::UNCLAIMED;
+use event::GameEvent;
+use super::{PlayerId, TerritoryId, NumArmies, Card};
+
+// the phases of a player's turn, in order
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    Trade,
+    Reinforce,
+    Attack,
+    Fortify,
+}
+
+// Something that watches a game as it is played. Observers are registered with
+// `GameManagerBuilder::observer` or `GameManager::add_observer`, and are told
+// about every `GameEvent` in the order they happen. `on_event` is called for
+// every event, followed by the specific callback for that kind of event, if
+// there is one. Every method does nothing by default, so an observer only has
+// to implement the ones it cares about.
+pub trait GameObserver {
+    fn on_event(&mut self, _event: &GameEvent) {}
+
+    fn on_turn_start(&mut self, _turn: usize, _player: PlayerId) {}
+
+    fn on_phase_change(&mut self, _player: PlayerId, _phase: Phase) {}
+
+    fn on_battle(&mut self,
+                 _player: PlayerId,
+                 _origin: TerritoryId,
+                 _target: TerritoryId,
+                 _attacker_losses: NumArmies,
+                 _defender_losses: NumArmies) {
+    }
+
+    fn on_conquest(&mut self,
+                   _player: PlayerId,
+                   _previous_owner: PlayerId,
+                   _origin: TerritoryId,
+                   _target: TerritoryId,
+                   _armies: NumArmies) {
+    }
+
+    fn on_card_drawn(&mut self, _player: PlayerId, _card: Card) {}
+
+    fn on_elimination(&mut self, _player: PlayerId, _by: PlayerId) {}
+
+    fn on_game_over(&mut self, _winner: Option<PlayerId>, _turns: usize) {}
+}
+
+// passes `event` on to the matching callbacks of `observer`
+pub fn notify(observer: &mut dyn GameObserver, event: &GameEvent) {
+    observer.on_event(event);
+
+    match *event {
+        GameEvent::TurnStarted { turn, player } => observer.on_turn_start(turn, player),
+        GameEvent::PhaseChanged { player, phase } => observer.on_phase_change(player, phase),
+        GameEvent::BattleResolved { player, origin, target, attacker_losses, defender_losses, .. } => {
+            observer.on_battle(player, origin, target, attacker_losses, defender_losses)
+        }
+        GameEvent::TerritoryConquered { player, previous_owner, origin, target, armies } => {
+            observer.on_conquest(player, previous_owner, origin, target, armies)
+        }
+        GameEvent::CardDrawn { player, card } => observer.on_card_drawn(player, card),
+        GameEvent::PlayerEliminated { player, by } => observer.on_elimination(player, by),
+        GameEvent::GameOver { winner, turns } => observer.on_game_over(winner, turns),
+        GameEvent::GameStarted { .. } |
+        GameEvent::TerritoryClaimed { .. } |
+        GameEvent::ArmyPlaced { .. } |
+        GameEvent::Trade { .. } |
+        GameEvent::Reinforced { .. } |
+        GameEvent::CardsCaptured { .. } |
+        GameEvent::BlitzResolved { .. } |
+        GameEvent::Fortified { .. } |
+        GameEvent::ActionRejected { .. } |
+        GameEvent::PlayerForfeited { .. } => {}
+    }
+}
+
+// Prints a running commentary of the game to stdout.
+pub struct ConsoleObserver;
+
+impl GameObserver for ConsoleObserver {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::GameStarted { num_players, neutral, ref territories, .. } => {
+                println!("Starting a game with {} players.", num_players);
+                if let Some(neutral) = neutral {
+                    println!("Player {} is neutral.", neutral);
+                }
+                for (i, &(owner, armies)) in territories.iter().enumerate() {
+                    if owner == UNCLAIMED {
+                        println!("{} is unclaimed", i);
+                    } else {
+                        println!("owner of {} is {} ({} armies)", i, owner, armies);
+                    }
+                }
+            }
+            GameEvent::TerritoryClaimed { player, territory } => {
+                println!("Player {} claimed territory {}", player, territory);
+            }
+            GameEvent::ArmyPlaced { player, territory } => {
+                println!("Player {} placed an army on territory {}", player, territory);
+            }
+            GameEvent::Trade { player, ref cards, armies, .. } => {
+                println!("Player {} is trading in {:?} for {} armies", player, cards, armies);
+            }
+            GameEvent::Reinforced { territory, armies, .. } => {
+                println!("  territory {} gained {} units", territory, armies);
+            }
+            GameEvent::BattleResolved { ref attacker_dice, ref defender_dice, .. }
+                if !attacker_dice.is_empty() => {
+                println!("  attacker rolled {:?}, defender rolled {:?}", attacker_dice, defender_dice);
+            }
+            GameEvent::BlitzResolved { player, origin, target, rounds, attacker_losses, defender_losses, conquered } => {
+                println!("Player {} blitzed {} from {} for {} rounds: attacker lost {}, defender lost {}{}",
+                         player,
+                         target,
+                         origin,
+                         rounds,
+                         attacker_losses,
+                         defender_losses,
+                         if conquered { ", and took it" } else { "" });
+            }
+            GameEvent::CardsCaptured { player, from, ref cards } => {
+                println!("Player {} took {} cards from player {}", player, cards.len(), from);
+            }
+            GameEvent::Fortified { player, origin, destination, armies } => {
+                println!("   !!! Player {} moved {} units from {} to {}",
+                         player,
+                         armies,
+                         origin,
+                         destination);
+            }
+            GameEvent::ActionRejected { player, reason } => {
+                println!("Player {} made an invalid choice: {}", player, reason);
+            }
+            GameEvent::PlayerForfeited { player } => {
+                println!("Player {} forfeited the game", player);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_turn_start(&mut self, turn: usize, player: PlayerId) {
+        println!("\nTurn {}: player {}", turn, player);
+        println!("==========");
+    }
+
+    fn on_battle(&mut self,
+                 player: PlayerId,
+                 origin: TerritoryId,
+                 target: TerritoryId,
+                 attacker_losses: NumArmies,
+                 defender_losses: NumArmies) {
+        println!("Player {} attacked {} from {}: attacker lost {}, defender lost {}",
+                 player,
+                 target,
+                 origin,
+                 attacker_losses,
+                 defender_losses);
+    }
+
+    fn on_conquest(&mut self,
+                   _player: PlayerId,
+                   _previous_owner: PlayerId,
+                   origin: TerritoryId,
+                   target: TerritoryId,
+                   armies: NumArmies) {
+        println!("Territory {} was conquered, moving {} units over from {}",
+                 target,
+                 armies,
+                 origin);
+    }
+
+    fn on_card_drawn(&mut self, player: PlayerId, _card: Card) {
+        println!("Player {} drew a card", player);
+    }
+
+    fn on_elimination(&mut self, player: PlayerId, by: PlayerId) {
+        println!("Player {} was eliminated by player {}", player, by);
+    }
+
+    fn on_game_over(&mut self, winner: Option<PlayerId>, turns: usize) {
+        match winner {
+            Some(winner) => println!("Player {} won the game after {} turns", winner, turns),
+            None => println!("The game ended without a winner after {} turns", turns),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{NumArmies, attacking_allowed, defending_allowed};
+
+// the chance of each outcome of one round of dice, as (attacker losses,
+// defender losses, probability), worked out exactly by going through every
+// way the dice can land
+fn round_outcomes(attacker_dice: usize, defender_dice: usize) -> Vec<(NumArmies, NumArmies, f64)> {
+    let num_dice = attacker_dice + defender_dice;
+    let num_rolls = 6usize.pow(num_dice as u32);
+
+    let mut counts: HashMap<(NumArmies, NumArmies), usize> = HashMap::new();
+    for roll in 0..num_rolls {
+        let mut dice: Vec<usize> = (0..num_dice).map(|i| roll / 6usize.pow(i as u32) % 6).collect();
+        let mut defender = dice.split_off(attacker_dice);
+        let mut attacker = dice;
+        attacker.sort_by(|a, b| b.cmp(a));
+        defender.sort_by(|a, b| b.cmp(a));
+
+        let mut losses = (0, 0);
+        for (a, d) in attacker.iter().zip(defender.iter()) {
+            if a > d {
+                losses.1 += 1;
+            } else {
+                losses.0 += 1;
+            }
+        }
+        *counts.entry(losses).or_insert(0) += 1;
+    }
+
+    let mut outcomes: Vec<_> = counts.into_iter()
+                                     .map(|((a, d), count)| (a, d, count as f64 / num_rolls as f64))
+                                     .collect();
+    outcomes.sort_by_key(|x| (x.0, x.1));
+    outcomes
+}
+
+// How a whole battle can end, where `attackers` armies keep attacking
+// `defenders` armies until one side has none left. `attackers` counts only
+// the armies able to attack, not the one that has to stay behind in the
+// attacking territory.
+#[derive(Clone, Debug)]
+pub struct BattleOdds {
+    pub attackers: NumArmies,
+    pub defenders: NumArmies,
+    // `attacker_left[k]` is the chance that the defenders are wiped out with
+    // `k` attackers left. `attacker_left[0]` is always 0
+    pub attacker_left: Vec<f64>,
+    // `defender_left[k]` is the chance that the attackers are wiped out with
+    // `k` defenders left. `defender_left[0]` is always 0
+    pub defender_left: Vec<f64>,
+}
+
+impl BattleOdds {
+    // the chance that the attacker conquers the territory
+    pub fn attacker_wins(&self) -> f64 {
+        self.attacker_left.iter().sum()
+    }
+
+    pub fn expected_attacker_losses(&self) -> f64 {
+        let when_winning: f64 = self.attacker_left
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(k, p)| p * (self.attackers as usize - k) as f64)
+                                    .sum();
+        let when_losing = (1. - self.attacker_wins()) * self.attackers as f64;
+        when_winning + when_losing
+    }
+
+    pub fn expected_defender_losses(&self) -> f64 {
+        let when_losing: f64 = self.defender_left
+                                   .iter()
+                                   .enumerate()
+                                   .map(|(k, p)| p * (self.defenders as usize - k) as f64)
+                                   .sum();
+        let when_winning = self.attacker_wins() * self.defenders as f64;
+        when_losing + when_winning
+    }
+}
+
+// The chances of an attack pushing through a chain of territories, each
+// attacked from the one conquered before it. Whatever survives a battle
+// moves into the conquered territory, and all but one of those armies go on
+// to attack the next.
+#[derive(Clone, Debug)]
+pub struct ChainOdds {
+    // `captured[i]` is the chance of conquering the first `i + 1` territories
+    pub captured: Vec<f64>,
+    // the expected number of armies lost by the attacker over the chain
+    pub expected_attacker_losses: f64,
+}
+
+// Works out battle odds, remembering every battle it has worked out, so that
+// asking again is cheap. The per-round odds are exact rather than rounded.
+pub struct OddsCalculator {
+    // indexed by the number of attacking and defending dice
+    rounds: Vec<Vec<Vec<(NumArmies, NumArmies, f64)>>>,
+    battles: RefCell<HashMap<(NumArmies, NumArmies), Rc<BattleOdds>>>,
+}
+
+impl Default for OddsCalculator {
+    fn default() -> OddsCalculator {
+        OddsCalculator::new()
+    }
+}
+
+impl OddsCalculator {
+    pub fn new() -> OddsCalculator {
+        let rounds = (0..4).map(|a| (0..3).map(|d| round_outcomes(a, d)).collect()).collect();
+        OddsCalculator {
+            rounds,
+            battles: RefCell::new(HashMap::new()),
+        }
+    }
+
+    // the chance of each outcome of a single round of dice between
+    // `attackers` and `defenders` armies, as (attacker losses, defender
+    // losses, probability)
+    pub fn round(&self, attackers: NumArmies, defenders: NumArmies) -> &[(NumArmies, NumArmies, f64)] {
+        let attacker_dice = attacking_allowed(attackers) as usize;
+        let defender_dice = defending_allowed(defenders) as usize;
+        &self.rounds[attacker_dice][defender_dice]
+    }
+
+    // the odds of `attackers` armies attacking `defenders` armies until one
+    // side has none left
+    pub fn battle(&self, attackers: NumArmies, defenders: NumArmies) -> Rc<BattleOdds> {
+        if let Some(odds) = self.battles.borrow().get(&(attackers, defenders)) {
+            return odds.clone();
+        }
+
+        let odds = Rc::new(self.work_out_battle(attackers, defenders));
+        self.battles.borrow_mut().insert((attackers, defenders), odds.clone());
+        odds
+    }
+
+    // the chance that `attackers` armies conquer a territory held by
+    // `defenders` armies
+    pub fn win_probability(&self, attackers: NumArmies, defenders: NumArmies) -> f64 {
+        self.battle(attackers, defenders).attacker_wins()
+    }
+
+    fn work_out_battle(&self, attackers: NumArmies, defenders: NumArmies) -> BattleOdds {
+        let a_max = attackers as usize;
+        let d_max = defenders as usize;
+
+        // the chance of the battle passing through each (attackers,
+        // defenders) state. every round takes at least one army off one side
+        // and none off the other, so going through the states from the top
+        // down handles every state before the ones it leads to
+        let mut state = vec![vec![0.; d_max + 1]; a_max + 1];
+        state[a_max][d_max] = 1.;
+        for a in (1..a_max + 1).rev() {
+            for d in (1..d_max + 1).rev() {
+                let p = state[a][d];
+                if p == 0. {
+                    continue;
+                }
+                for &(a_loss, d_loss, q) in self.round(a as NumArmies, d as NumArmies) {
+                    state[a - a_loss as usize][d - d_loss as usize] += p * q;
+                }
+            }
+        }
+
+        BattleOdds {
+            attackers,
+            defenders,
+            attacker_left: (0..a_max + 1).map(|a| if a == 0 { 0. } else { state[a][0] }).collect(),
+            defender_left: (0..d_max + 1).map(|d| if d == 0 { 0. } else { state[0][d] }).collect(),
+        }
+    }
+
+    // the odds of `attackers` armies pushing through territories held by
+    // each of `defenders` in turn
+    pub fn chain(&self, attackers: NumArmies, defenders: &[NumArmies]) -> ChainOdds {
+        // the chance of having each number of armies able to attack the
+        // next territory
+        let mut armies = vec![0.; attackers as usize + 1];
+        armies[attackers as usize] = 1.;
+
+        let mut captured = Vec::new();
+        let mut expected_attacker_losses = 0.;
+        for &target in defenders.iter() {
+            let mut next = vec![0.; armies.len()];
+            for (a, &p) in armies.iter().enumerate() {
+                if a == 0 || p == 0. {
+                    continue;
+                }
+
+                let odds = self.battle(a as NumArmies, target);
+                expected_attacker_losses += p * odds.expected_attacker_losses();
+                for (left, &q) in odds.attacker_left.iter().enumerate().skip(1) {
+                    // one army stays behind in the conquered territory
+                    next[left - 1] += p * q;
+                }
+            }
+
+            captured.push(next.iter().sum());
+            armies = next;
+            if captured.last().cloned().unwrap_or(0.) == 0. {
+                break;
+            }
+        }
+        // nothing gets further than a territory that can't be taken
+        let len = defenders.len();
+        captured.resize(len, 0.);
+
+        ChainOdds {
+            captured,
+            expected_attacker_losses,
+        }
+    }
+}
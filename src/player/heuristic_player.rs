@@ -0,0 +1,263 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use super::Player;
+use ::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories};
+use ::{GameBoard, Trade, Reinforcement, Attack, AttackOption, Move};
+use ::{Continent, OddsCalculator, attack_options, legal_trades};
+
+// by default the player only attacks when it has at least this chance of
+// taking the target
+pub const DEFAULT_MIN_WIN: f64 = 0.6;
+
+// A computer player that makes the same decision every time it sees the same
+// board. It puts all of its reinforcements on the border territory with the
+// best target next to it, attacks until the target falls whenever the odds
+// are good enough, goes after the continents it nearly holds, and moves
+// armies out of its interior towards the frontier.
+pub struct HeuristicPlayer {
+    min_win: f64,
+    odds: OddsCalculator,
+}
+
+impl Default for HeuristicPlayer {
+    fn default() -> HeuristicPlayer {
+        HeuristicPlayer::new()
+    }
+}
+
+impl HeuristicPlayer {
+    pub fn new() -> HeuristicPlayer {
+        HeuristicPlayer::with_min_win(DEFAULT_MIN_WIN)
+    }
+
+    // a player that attacks only with at least `min_win` chance of taking
+    // the target
+    pub fn with_min_win(min_win: f64) -> HeuristicPlayer {
+        HeuristicPlayer {
+            min_win,
+            odds: OddsCalculator::new(),
+        }
+    }
+
+    pub fn make_heuristic_players(number: usize) -> Vec<Box<dyn Player>> {
+        let mut players: Vec<Box<dyn Player>> = vec![];
+        for _ in 0..number {
+            players.push(Box::new(HeuristicPlayer::new()));
+        }
+        players
+    }
+
+    // how much `player` wants `target`: more if its continent is mostly
+    // theirs already, and most if taking it completes the continent
+    fn target_value(&self, board: &dyn GameBoard, player: PlayerId, target: TerritoryId) -> f64 {
+        let continent = match continent_of(board, target) {
+            None => return 1.,
+            Some(continent) => continent,
+        };
+
+        let owned = continent.territories
+                             .iter()
+                             .filter(|&&terr| board.get_owner(terr) == player)
+                             .count();
+        let size = continent.territories.len();
+        let share = owned as f64 / size as f64;
+        let completes = if owned + 1 == size { 1. + continent.bonus as f64 / 2. } else { 0. };
+        1. + 2. * share + completes
+    }
+
+    // the border territory of `player` that the next army is most useful on,
+    // or their first territory if none of them border an enemy
+    fn reinforcement_target(&self, board: &dyn GameBoard, player: PlayerId) -> Option<TerritoryId> {
+        let owned = board.get_owned_territories(player);
+
+        let mut best: Option<(f64, TerritoryId)> = None;
+        for &terr in owned.iter() {
+            // the most attractive target this territory could attack, given
+            // the armies defending it
+            let score = enemy_neighbors(board, player, terr)
+                            .into_iter()
+                            .map(|enemy| {
+                                self.target_value(board, player, enemy) /
+                                (1. + board.get_num_armies(enemy) as f64)
+                            })
+                            .fold(None, |best: Option<f64>, score| {
+                                Some(best.map_or(score, |best| best.max(score)))
+                            });
+            if let Some(score) = score {
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, terr));
+                }
+            }
+        }
+
+        best.map(|(_, terr)| terr).or_else(|| owned.first().cloned())
+    }
+}
+
+// the continent that `terr` is part of, if any
+fn continent_of(board: &dyn GameBoard, terr: TerritoryId) -> Option<&Continent> {
+    board.continents().iter().find(|continent| continent.territories.contains(&terr))
+}
+
+fn enemy_neighbors(board: &dyn GameBoard, player: PlayerId, terr: TerritoryId) -> Vec<TerritoryId> {
+    board.game_map()
+         .get_neighbors(terr)
+         .into_iter()
+         .filter(|&neighbor| board.is_enemy_territory(player, neighbor))
+         .collect()
+}
+
+// how many steps each of `player`'s territories is from one that borders an
+// enemy, going through their own territories. territories that can't reach
+// the frontier are left out
+fn distances_to_frontier(board: &dyn GameBoard, player: PlayerId) -> BTreeMap<TerritoryId, usize> {
+    let mut distances = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    for terr in board.get_owned_territories(player) {
+        if !enemy_neighbors(board, player, terr).is_empty() {
+            distances.insert(terr, 0);
+            queue.push_back(terr);
+        }
+    }
+
+    while let Some(terr) = queue.pop_front() {
+        let distance = distances[&terr];
+        for neighbor in board.game_map().get_neighbors(terr) {
+            if board.get_owner(neighbor) == player && !distances.contains_key(&neighbor) {
+                distances.insert(neighbor, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    distances
+}
+
+impl Player for HeuristicPlayer {
+    fn claim_territory(&self,
+                       player: PlayerId,
+                       unclaimed: &[TerritoryId],
+                       board: &dyn GameBoard)
+                       -> TerritoryId {
+        // build up the continent where the player has the largest share,
+        // preferring small continents, which are easier to hold
+        let mut best: Option<(f64, TerritoryId)> = None;
+        for &terr in unclaimed.iter() {
+            let score = match continent_of(board, terr) {
+                None => 0.,
+                Some(continent) => {
+                    let owned = continent.territories
+                                         .iter()
+                                         .filter(|&&t| board.get_owner(t) == player)
+                                         .count();
+                    (owned as f64 + 1.) / continent.territories.len() as f64
+                }
+            };
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, terr));
+            }
+        }
+        best.map_or(unclaimed[0], |(_, terr)| terr)
+    }
+
+    fn place_army(&self, player: PlayerId, _remaining: NumArmies, board: &dyn GameBoard) -> TerritoryId {
+        self.reinforcement_target(board, player).unwrap_or(0)
+    }
+
+    fn make_trade(&self,
+                  cards: &[CardAndId],
+                  _other_reinf: NumArmies,
+                  _next_set_value: Option<NumArmies>,
+                  _necessary: bool)
+                  -> Option<Trade> {
+        // always trade, keeping wilds for later if possible
+        legal_trades(cards).into_iter()
+                           .min_by_key(|trade| trade.cards.iter().filter(|&&(card, _)| card.is_wild()).count())
+    }
+
+    fn distrib_reinforcements(&self,
+                              reinf: NumArmies,
+                              owned: &[TerritoryId],
+                              board: &dyn GameBoard)
+                              -> Reinforcement {
+        let mut terr_reinf = BTreeMap::new();
+        if let Some(&first) = owned.first() {
+            let player = board.get_owner(first);
+            let terr = self.reinforcement_target(board, player).unwrap_or(first);
+            terr_reinf.insert(terr, reinf);
+        }
+        Reinforcement::new(terr_reinf)
+    }
+
+    fn make_attack(&self, terr_info: &AttackTerritories, board: &dyn GameBoard) -> Option<Attack> {
+        let player = match terr_info.values().next() {
+            None => return None,
+            Some(info) => board.get_owner(info.id),
+        };
+
+        let mut best: Option<(f64, AttackOption)> = None;
+        for option in attack_options(board, player) {
+            let armies = board.get_num_armies(option.origin);
+            let win = self.odds.win_probability(armies - 1, board.get_num_armies(option.target));
+            if win < self.min_win {
+                continue;
+            }
+            let score = win * self.target_value(board, player, option.target);
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, option));
+            }
+        }
+
+        best.map(|(_, option)| Attack::blitz(option.origin, option.target, option.max_dice, 1))
+    }
+
+    fn make_combat_move(&self,
+                        origin: TerritoryId,
+                        target: TerritoryId,
+                        min: NumArmies,
+                        max: NumArmies,
+                        board: &dyn GameBoard)
+                        -> NumArmies {
+        // keep half of the armies back if the origin still has other enemies
+        // next to it, otherwise push everything forward
+        let player = board.get_owner(origin);
+        let threatened = enemy_neighbors(board, player, origin).into_iter().any(|terr| terr != target);
+        if threatened {
+            (max / 2).max(min)
+        } else {
+            max
+        }
+    }
+
+    fn fortify(&self, player: PlayerId, board: &dyn GameBoard) -> Option<Move> {
+        let distances = distances_to_frontier(board, player);
+
+        // the interior territory with the most armies to spare, moving them
+        // one step closer to the frontier
+        let mut best: Option<(NumArmies, TerritoryId, TerritoryId)> = None;
+        for (&terr, &distance) in distances.iter() {
+            let spare = board.get_num_armies(terr).saturating_sub(1);
+            if distance == 0 || spare == 0 {
+                continue;
+            }
+
+            let destination = board.game_map()
+                                   .get_neighbors(terr)
+                                   .into_iter()
+                                   .filter(|neighbor| distances.get(neighbor) == Some(&(distance - 1)))
+                                   .min();
+            if let Some(destination) = destination {
+                if best.is_none_or(|(best_spare, _, _)| spare > best_spare) {
+                    best = Some((spare, terr, destination));
+                }
+            }
+        }
+
+        best.map(|(amount, origin, destination)| {
+            Move {
+                origin,
+                destination,
+                amount,
+            }
+        })
+    }
+}
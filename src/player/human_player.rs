@@ -1,38 +1,58 @@
-use std::ascii::AsciiExt;
-use std::collections::HashMap;
-use std::io::{self, BufRead, Read, Write};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
 use std::str::FromStr;
 
 use super::Player;
 use ::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories};
-use ::{GameBoard, Trade, Reinforcement, Attack, Move};
+use ::{GameBoard, Trade, Reinforcement, Attack, AttackOutcome, Move, Rejection};
+use ::attack_options;
 
 pub struct HumanPlayer;
 
 // helper function for <HumanPlayer as Player>::make_trade
 fn prompt_for_trade_cards() -> [usize; 3] {
     let mut idxs = [0, 0, 0];
-    for i in 0..3 {
-        loop {
-            match prompt_and_parse::<usize>("Enter index of card to trade: ") {
-                Err(_) => continue,
-                Ok(index) => {
-                    idxs[i] = index;
-                }
-            }
-        }
+    for idx in idxs.iter_mut() {
+        *idx = repeatedly_prompt_and_parse::<usize>("Enter index of card to trade: ");
     }
     idxs
 }
 
 impl Player for HumanPlayer {
-    fn make_trade(&self, cards: &[CardAndId], other_reinf: NumArmies, necessary: bool) -> Option<Trade> {
+    fn claim_territory(&self,
+                       _player: PlayerId,
+                       unclaimed: &[TerritoryId],
+                       _board: &dyn GameBoard)
+                       -> TerritoryId {
+        println!("Unclaimed territories: {:?}", unclaimed);
+        repeatedly_prompt_and_parse::<TerritoryId>("Territory to claim: ")
+    }
+
+    fn place_army(&self, player: PlayerId, remaining: NumArmies, board: &dyn GameBoard) -> TerritoryId {
+        println!("Starting armies left to place: {}", remaining);
+        println!("Owned territories:");
+        for terr in board.get_owned_territories(player) {
+            print!("{}({}) ", terr, board.get_num_armies(terr));
+        }
+        println!();
+        repeatedly_prompt_and_parse::<TerritoryId>("Territory to place an army on: ")
+    }
+
+    fn make_trade(&self,
+                  cards: &[CardAndId],
+                  other_reinf: NumArmies,
+                  next_set_value: Option<NumArmies>,
+                  necessary: bool)
+                  -> Option<Trade> {
         println!("Cards:");
         for card in cards.iter() {
             println!("{:?}", card);
         }
 
         println!("Reinforcement from territory: {}", other_reinf);
+        if let Some(value) = next_set_value {
+            println!("Next set is worth: {}", value);
+        }
         println!("Necessary: {}", necessary);
 
         // prompt user to enter indices via command line
@@ -56,7 +76,8 @@ impl Player for HumanPlayer {
 
     fn distrib_reinforcements(&self,
                               reinf_amt: NumArmies,
-                              owned: &[TerritoryId])
+                              owned: &[TerritoryId],
+                              _board: &dyn GameBoard)
                               -> Reinforcement {
         println!("Reinforcements to distribute: {}", reinf_amt);
 
@@ -64,10 +85,10 @@ impl Player for HumanPlayer {
         for terr in owned.iter() {
             print!("{:?} ", terr);
         }
-        println!("");
+        println!();
         flush_stdout();
 
-        let mut reinf = HashMap::new();
+        let mut reinf = BTreeMap::new();
 
         let mut reinf_avail = reinf_amt;
         loop {
@@ -88,7 +109,7 @@ impl Player for HumanPlayer {
 
                                 reinf_avail -= num_armies;
 
-                                if let Some(old_num_armies) = reinf.get(&terr).map(|&x| x) {
+                                if let Some(old_num_armies) = reinf.get(&terr).cloned() {
                                     reinf.insert(terr, num_armies + old_num_armies);
                                 } else {
                                     reinf.insert(terr, num_armies);
@@ -105,13 +126,17 @@ impl Player for HumanPlayer {
         Reinforcement::new(reinf)
     }
 
-    fn make_attack(&self, terr_info: &AttackTerritories) -> Option<Attack> {
-        // print out info
-        for info in terr_info.values() {
-            println!("Territory {} has {} units and adjacent enemies {:?}",
-                     info.id, info.armies, info.adj_enemies.iter()
-                                                           .map(|&x| x)
-                                                           .collect::<Vec<_>>());
+    fn make_attack(&self, terr_info: &AttackTerritories, board: &dyn GameBoard) -> Option<Attack> {
+        // print out the attacks the engine will accept
+        if let Some(info) = terr_info.values().next() {
+            for option in attack_options(board, board.get_owner(info.id)) {
+                println!("Territory {} ({} units) can attack territory {} ({} units) with up to {} armies",
+                         option.origin,
+                         board.get_num_armies(option.origin),
+                         option.target,
+                         board.get_num_armies(option.target),
+                         option.max_dice);
+            }
         }
 
         // ask if user wants to make an attack
@@ -129,9 +154,12 @@ impl Player for HumanPlayer {
                         // TODO: this would be friendlier if checked whether the amount was within
                         // the available amount of units that can be attacked with
                         let num_armies = repeatedly_prompt_and_parse::<NumArmies>(" Number of armies: ");
-                        return Some(Attack::new(origin,
-                                                target,
-                                                num_armies));
+                        let stop_at = repeatedly_prompt_and_parse::<NumArmies>(
+                            " Keep attacking until the origin is down to (0 for a single roll): ");
+                        if stop_at == 0 {
+                            return Some(Attack::new(origin, target, num_armies));
+                        }
+                        return Some(Attack::blitz(origin, target, num_armies, stop_at));
                     }
                     _ => continue,
                 }
@@ -140,11 +168,35 @@ impl Player for HumanPlayer {
     }
 
 
-    fn make_combat_move(&self) -> Move {
-        unimplemented!()
+    fn attack_resolved(&self, attack: &Attack, outcome: &AttackOutcome) {
+        if outcome.rounds > 1 {
+            println!("After {} rounds on territory {}: you lost {}, the defender lost {}",
+                     outcome.rounds,
+                     attack.target,
+                     outcome.attacker_losses,
+                     outcome.defender_losses);
+        }
     }
 
-    fn fortify(&self, player: PlayerId, board: &GameBoard) -> Option<Move> {
+    fn make_combat_move(&self,
+                        origin: TerritoryId,
+                        target: TerritoryId,
+                        min: NumArmies,
+                        max: NumArmies,
+                        _board: &dyn GameBoard)
+                        -> NumArmies {
+        println!("Territory {} was conquered from {}", target, origin);
+        loop {
+            let msg = format!("Number of armies to move in ({}-{}): ", min, max);
+            let num_armies = repeatedly_prompt_and_parse::<NumArmies>(&msg);
+            if num_armies >= min && num_armies <= max {
+                return num_armies;
+            }
+            println!("Choose between {} and {} armies.", min, max);
+        }
+    }
+
+    fn fortify(&self, _player: PlayerId, _board: &dyn GameBoard) -> Option<Move> {
         loop {
             let input = prompt("Fortify? (y/n):").trim().to_ascii_lowercase();
             if input.len() == 1 {
@@ -158,7 +210,7 @@ impl Player for HumanPlayer {
                         // the available amount of units that can be moved
                         let num_armies = repeatedly_prompt_and_parse::<NumArmies>(" Number of armies to move: ");
                         return Some(Move {
-                            origin: origin,
+                            origin,
                             destination: dest,
                             amount: num_armies,
                         });
@@ -168,7 +220,10 @@ impl Player for HumanPlayer {
             }
         }
     }
-    
+
+    fn action_rejected(&self, reason: Rejection) {
+        println!("That isn't allowed: {}. Choose again.", reason);
+    }
 }
 
 // panics if it couldn't flush it
@@ -188,7 +243,7 @@ fn repeatedly_prompt_and_parse<T: FromStr>(msg: &str) -> T {
 
 
 fn prompt_and_parse<T: FromStr>(msg: &str) -> Result<T, <T as FromStr>::Err> {
-    let mut input = prompt(msg);
+    let input = prompt(msg);
     input.trim().parse::<T>()
 }
 
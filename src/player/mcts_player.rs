@@ -0,0 +1,415 @@
+use rand::Rng;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use super::{Player, HeuristicPlayer};
+use ::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories};
+use ::{GameBoard, Trade, Reinforcement, Attack, Move, Rules, CardManager, UNCLAIMED};
+use ::{Action, GameState, TurnStage, Evaluator, WeightedEvaluator};
+use ::{attacking_allowed, seeded_rng, GameRng};
+
+// by default a decision gets this many rollouts
+pub const DEFAULT_ITERATIONS: usize = 300;
+
+// by default a rollout plays this many turns before the position is scored
+pub const DEFAULT_ROLLOUT_TURNS: usize = 4;
+
+// How much searching a `MctsPlayer` does for each decision.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchBudget {
+    // this many rollouts, which makes the player's decisions depend only on
+    // its seed
+    Iterations(usize),
+    // as many rollouts as fit in this much time, but at least one
+    Time(Duration),
+}
+
+impl Default for SearchBudget {
+    fn default() -> SearchBudget {
+        SearchBudget::Iterations(DEFAULT_ITERATIONS)
+    }
+}
+
+// the UCT exploration constant
+const EXPLORATION: f64 = 0.7;
+
+// rewards shrink by this much for every turn a rollout plays
+const DISCOUNT: f64 = 0.9;
+
+// A node of the search tree. Dice and cards make the same actions lead to
+// different states, so a node stands for the actions taken to reach it rather
+// than a single state, and its children are only those actions that are
+// legal in the state reached this time.
+struct Node {
+    action: Action,
+    // the player who took `action`, whose rewards this node adds up
+    mover: PlayerId,
+    visits: f64,
+    reward: f64,
+    children: Vec<usize>,
+}
+
+// A computer player that decides its reinforcements, attacks, combat moves
+// and fortification with Monte Carlo tree search over a `GameState`. Every
+// rollout plays a few turns with a cheap random policy and scores the end
+// position with a `WeightedEvaluator`. It only sees the board, so the search
+// assumes that nobody holds any cards and that everybody who holds
+// territories takes turns, a neutral player included. Territories are
+// claimed, armies placed and sets traded as a `HeuristicPlayer` would.
+pub struct MctsPlayer {
+    budget: SearchBudget,
+    rollout_turns: usize,
+    evaluator: WeightedEvaluator,
+    heuristic: HeuristicPlayer,
+
+    // the `Player` methods take `&self`, so the RNG needs interior mutability
+    rng: RefCell<GameRng>,
+}
+
+impl MctsPlayer {
+    // a player searching within `budget`, for a game played by `rules`, that
+    // draws every rollout from `rng`
+    pub fn new(budget: SearchBudget, rules: Rules, rng: GameRng) -> MctsPlayer {
+        MctsPlayer {
+            budget,
+            rollout_turns: DEFAULT_ROLLOUT_TURNS,
+            evaluator: WeightedEvaluator::new(rules),
+            heuristic: HeuristicPlayer::new(),
+            rng: RefCell::new(rng),
+        }
+    }
+
+    pub fn from_seed(budget: SearchBudget, rules: Rules, seed: u64) -> MctsPlayer {
+        MctsPlayer::new(budget, rules, seeded_rng(seed))
+    }
+
+    // returns a vector of MCTS players, each seeded from `rng`
+    pub fn make_mcts_players<R: Rng>(number: usize,
+                                     budget: SearchBudget,
+                                     rules: &Rules,
+                                     rng: &mut R)
+                                     -> Vec<Box<dyn Player>> {
+        let mut players: Vec<Box<dyn Player>> = vec![];
+        for _ in 0..number {
+            players.push(Box::new(MctsPlayer::from_seed(budget, rules.clone(), rng.gen())));
+        }
+        players
+    }
+
+    // the state the search starts from, with `player` at `stage` of their
+    // turn on `board`
+    fn root_state(&self, board: &dyn GameBoard, player: PlayerId, stage: TurnStage) -> Option<GameState> {
+        let num_territories = board.game_map().num_territories();
+        let num_players = (0..num_territories)
+                              .map(|terr| board.get_owner(terr as TerritoryId))
+                              .filter(|&owner| owner != UNCLAIMED)
+                              .max()
+                              .map_or(0, |owner| owner as usize + 1);
+        if player as usize >= num_players {
+            return None;
+        }
+
+        let cards = CardManager::standard_card_manager(num_players,
+                                                       num_territories,
+                                                       &mut *self.rng.borrow_mut());
+        GameState::new(board, self.evaluator.rules.clone(), cards)
+            .ok()
+            .map(|state| state.at_stage(player, stage))
+    }
+
+    // the action the search rates best for the current player of `root`
+    fn search(&self, root: &GameState) -> Option<Action> {
+        let actions = candidate_actions(root);
+        if actions.len() <= 1 {
+            return actions.first().cloned();
+        }
+
+        let mut rng = self.rng.borrow_mut();
+        // the root's action and mover are never looked at
+        let mut tree = vec![Node {
+            action: actions[0],
+            mover: root.current_player(),
+            visits: 0.,
+            reward: 0.,
+            children: Vec::new(),
+        }];
+
+        let start = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let done = match self.budget {
+                SearchBudget::Iterations(n) => iterations >= n,
+                SearchBudget::Time(limit) => iterations > 0 && start.elapsed() >= limit,
+            };
+            if done {
+                break;
+            }
+            iterations += 1;
+
+            let mut state = root.clone();
+            let path = self.select_and_expand(&mut tree, &mut state, &mut rng);
+            let rewards = self.rollout(&mut state, root.turn(), &mut rng);
+
+            tree[0].visits += 1.;
+            for &i in path.iter() {
+                let node = &mut tree[i];
+                node.visits += 1.;
+                node.reward += rewards.get(node.mover as usize).cloned().unwrap_or(0.);
+            }
+        }
+
+        tree[0].children
+               .iter()
+               .map(|&i| &tree[i])
+               .fold(None, |best: Option<&Node>, node| {
+                   match best {
+                       Some(best) if best.visits >= node.visits => Some(best),
+                       _ => Some(node),
+                   }
+               })
+               .map(|node| node.action)
+    }
+
+    // walks down the tree from the root, taking the action with the best
+    // UCT score among those legal in `state`, until it reaches a node with
+    // an untried action, which it takes and adds to the tree. returns the
+    // nodes visited, not counting the root
+    fn select_and_expand(&self, tree: &mut Vec<Node>, state: &mut GameState, rng: &mut GameRng) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = 0;
+        while !state.is_game_over() {
+            let actions = candidate_actions(state);
+            let mover = state.current_player();
+
+            let untried: Vec<Action> = actions.iter()
+                                              .filter(|&&action| {
+                                                  tree[current].children
+                                                               .iter()
+                                                               .all(|&i| tree[i].action != action)
+                                              })
+                                              .cloned()
+                                              .collect();
+            if !untried.is_empty() {
+                let action = untried[rng.gen_range(0, untried.len())];
+                if state.apply(action, rng).is_err() {
+                    break;
+                }
+                tree.push(Node {
+                    action,
+                    mover,
+                    visits: 0.,
+                    reward: 0.,
+                    children: Vec::new(),
+                });
+                let child = tree.len() - 1;
+                tree[current].children.push(child);
+                path.push(child);
+                break;
+            }
+
+            let parent_visits = tree[current].visits.max(1.);
+            let mut best: Option<(f64, usize)> = None;
+            for &i in tree[current].children.iter() {
+                let node = &tree[i];
+                if !actions.contains(&node.action) {
+                    continue;
+                }
+                let score = node.reward / node.visits.max(1.) +
+                            EXPLORATION * (parent_visits.ln() / node.visits.max(1.)).sqrt();
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, i));
+                }
+            }
+            let child = match best {
+                None => break,
+                Some((_, child)) => child,
+            };
+            if state.apply(tree[child].action, rng).is_err() {
+                break;
+            }
+            path.push(child);
+            current = child;
+        }
+        path
+    }
+
+    // plays on from `state` until the game is over or `rollout_turns` turns
+    // after `start_turn`, and returns every player's reward, from 0 to 1
+    fn rollout(&self, state: &mut GameState, start_turn: usize, rng: &mut GameRng) -> Vec<f64> {
+        while !state.is_game_over() && state.turn() < start_turn + self.rollout_turns {
+            let actions = candidate_actions(state);
+            let action = match rollout_action(state, &actions, rng) {
+                None => break,
+                Some(action) => action,
+            };
+            if state.apply(action, rng).is_err() {
+                break;
+            }
+        }
+
+        // the sooner the better, so that a player who is sure to win goes
+        // ahead and does
+        let discount = DISCOUNT.powi(state.turn().saturating_sub(start_turn) as i32);
+
+        let num_players = state.num_players();
+        if let Some(winner) = state.winner() {
+            return (0..num_players).map(|p| if p == winner as usize { discount } else { 0. }).collect();
+        }
+
+        // everyone's share of the total score
+        let scores: Vec<f64> = self.evaluator
+                                   .scores(state, num_players)
+                                   .into_iter()
+                                   .map(|score| score.max(0.))
+                                   .collect();
+        let total: f64 = scores.iter().sum();
+        if total > 0. {
+            scores.iter().map(|score| discount * score / total).collect()
+        } else {
+            vec![0.; num_players]
+        }
+    }
+
+    // runs a search for `player` at `stage` of their turn
+    fn decide(&self, board: &dyn GameBoard, player: PlayerId, stage: TurnStage) -> Option<Action> {
+        self.root_state(board, player, stage).and_then(|root| self.search(&root))
+    }
+}
+
+// the legal actions worth searching: attacks only with as many dice as
+// possible, and reinforcements only on territories next to an enemy, unless
+// there are none
+fn candidate_actions(state: &GameState) -> Vec<Action> {
+    let player = state.current_player();
+    let borders = |terr: TerritoryId| {
+        state.game_map()
+             .get_neighbors(terr)
+             .into_iter()
+             .any(|neighbor| state.is_enemy_territory(player, neighbor))
+    };
+
+    let actions = state.legal_actions();
+    let candidates: Vec<Action> = actions.iter()
+                                         .filter(|&&action| match action {
+                                             Action::Attack { origin, dice, .. } => {
+                                                 dice == attacking_allowed(state.get_num_armies(origin) - 1)
+                                             }
+                                             Action::Reinforce { territory, .. } => borders(territory),
+                                             _ => true,
+                                         })
+                                         .cloned()
+                                         .collect();
+    if candidates.is_empty() { actions } else { candidates }
+}
+
+// the rollout policy: a random action, except that attacks are only made
+// with more armies than the target has, and the attacks end once there are
+// none of those left
+fn rollout_action(state: &GameState, actions: &[Action], rng: &mut GameRng) -> Option<Action> {
+    if let TurnStage::Attack = state.stage() {
+        let attacks: Vec<Action> = actions.iter()
+                                          .filter(|&&action| match action {
+                                              Action::Attack { origin, target, .. } => {
+                                                  state.get_num_armies(origin) - 1 > state.get_num_armies(target)
+                                              }
+                                              _ => false,
+                                          })
+                                          .cloned()
+                                          .collect();
+        if attacks.is_empty() {
+            return Some(Action::EndAttack);
+        }
+        return Some(attacks[rng.gen_range(0, attacks.len())]);
+    }
+
+    if actions.is_empty() {
+        None
+    } else {
+        Some(actions[rng.gen_range(0, actions.len())])
+    }
+}
+
+impl Player for MctsPlayer {
+    fn claim_territory(&self,
+                       player: PlayerId,
+                       unclaimed: &[TerritoryId],
+                       board: &dyn GameBoard)
+                       -> TerritoryId {
+        self.heuristic.claim_territory(player, unclaimed, board)
+    }
+
+    fn place_army(&self, player: PlayerId, remaining: NumArmies, board: &dyn GameBoard) -> TerritoryId {
+        self.heuristic.place_army(player, remaining, board)
+    }
+
+    fn make_trade(&self,
+                  cards: &[CardAndId],
+                  other_reinf: NumArmies,
+                  next_set_value: Option<NumArmies>,
+                  necessary: bool)
+                  -> Option<Trade> {
+        self.heuristic.make_trade(cards, other_reinf, next_set_value, necessary)
+    }
+
+    fn distrib_reinforcements(&self,
+                              reinf: NumArmies,
+                              owned: &[TerritoryId],
+                              board: &dyn GameBoard)
+                              -> Reinforcement {
+        let player = match owned.first() {
+            None => return Reinforcement::new(BTreeMap::new()),
+            Some(&first) => board.get_owner(first),
+        };
+
+        let stage = TurnStage::Reinforce { armies: reinf, mid_attack: false };
+        match self.decide(board, player, stage) {
+            Some(Action::Reinforce { territory, .. }) => {
+                let mut terr_reinf = BTreeMap::new();
+                terr_reinf.insert(territory, reinf);
+                Reinforcement::new(terr_reinf)
+            }
+            _ => self.heuristic.distrib_reinforcements(reinf, owned, board),
+        }
+    }
+
+    fn make_attack(&self, terr_info: &AttackTerritories, board: &dyn GameBoard) -> Option<Attack> {
+        let player = match terr_info.values().next() {
+            None => return None,
+            Some(info) => board.get_owner(info.id),
+        };
+
+        match self.decide(board, player, TurnStage::Attack) {
+            Some(Action::Attack { origin, target, dice }) => Some(Attack::new(origin, target, dice)),
+            _ => None,
+        }
+    }
+
+    fn make_combat_move(&self,
+                        origin: TerritoryId,
+                        target: TerritoryId,
+                        min: NumArmies,
+                        max: NumArmies,
+                        board: &dyn GameBoard)
+                        -> NumArmies {
+        let player = board.get_owner(origin);
+        let stage = TurnStage::CombatMove { origin, target, min, max };
+        match self.decide(board, player, stage) {
+            Some(Action::CombatMove(armies)) => armies,
+            _ => self.heuristic.make_combat_move(origin, target, min, max, board),
+        }
+    }
+
+    fn fortify(&self, player: PlayerId, board: &dyn GameBoard) -> Option<Move> {
+        match self.decide(board, player, TurnStage::Fortify { moves_left: 1 }) {
+            Some(Action::Fortify { origin, destination, armies }) => {
+                Some(Move {
+                    origin,
+                    destination,
+                    amount: armies,
+                })
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,30 +1,77 @@
 pub use self::random_player::RandomPlayer;
 pub use self::human_player::HumanPlayer;
+pub use self::heuristic_player::HeuristicPlayer;
+pub use self::mcts_player::{MctsPlayer, SearchBudget};
 use super::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories};
-use super::{GameBoard, Trade, Reinforcement, Attack, Move};
+use super::{GameBoard, Trade, Reinforcement, Attack, AttackOutcome, Move, Rejection};
 
 mod random_player;
 mod human_player;
+mod heuristic_player;
+mod mcts_player;
 
 pub trait Player {
-    // called at the beginning of the turn, prompts the player to turn in a set
-    fn make_trade(&self, cards: &[CardAndId], other_reinf: NumArmies, necessary: bool) -> Option<Trade>;
+    // called during a draft, before the first turn. prompts the player to
+    // claim one of the `unclaimed` territories. `board` shows the territories
+    // claimed so far; the owner of an unclaimed territory is `UNCLAIMED`
+    fn claim_territory(&self,
+                       player: PlayerId,
+                       unclaimed: &[TerritoryId],
+                       board: &dyn GameBoard)
+                       -> TerritoryId;
+
+    // called during setup, before the first turn. prompts the player to place
+    // one of `player`'s `remaining` starting armies on a territory `player`
+    // owns. `player` is usually this player, but in a game with a neutral
+    // player the real players are also asked to place the neutral armies
+    fn place_army(&self, player: PlayerId, remaining: NumArmies, board: &dyn GameBoard) -> TerritoryId;
+
+    // called at the beginning of the turn, prompts the player to turn in a set.
+    // `next_set_value` is what the next set traded is worth, if that doesn't
+    // depend on the cards in it
+    fn make_trade(&self,
+                  cards: &[CardAndId],
+                  other_reinf: NumArmies,
+                  next_set_value: Option<NumArmies>,
+                  necessary: bool)
+                  -> Option<Trade>;
 
     // called after a potential set trade, prompts the player to distribute
     // available reinforcements
-    fn distrib_reinforcements(&self, NumArmies, &[TerritoryId]) -> Reinforcement;
+    fn distrib_reinforcements(&self,
+                              reinf: NumArmies,
+                              owned: &[TerritoryId],
+                              board: &dyn GameBoard)
+                              -> Reinforcement;
 
     // called after reinforcements are distributed, prompts player to make an attack
     // takes a slice where each element is an information data structure corresponding
     // to one of the territories that the player owns.
-    fn make_attack(&self, &AttackTerritories) -> Option<Attack>;
+    fn make_attack(&self, terr_info: &AttackTerritories, board: &dyn GameBoard) -> Option<Attack>;
 
     // called if an attack succeeds. prompts the player to move available armies
-    // from the attacking territory to the newly occupied territory
-    fn make_combat_move(&self) -> Move;
+    // from the attacking territory to the newly occupied territory. returns
+    // the number of armies to move, which has to be between `min` (the armies
+    // that survived the battle) and `max` (all but one of the armies on
+    // `origin`)
+    fn make_combat_move(&self,
+                        origin: TerritoryId,
+                        target: TerritoryId,
+                        min: NumArmies,
+                        max: NumArmies,
+                        board: &dyn GameBoard)
+                        -> NumArmies;
 
     // called once per turn after all attacks are completed. prompts the user to
     // fortify a territory
-    fn fortify(&self, PlayerId, &GameBoard) -> Option<Move>;
+    fn fortify(&self, player: PlayerId, board: &dyn GameBoard) -> Option<Move>;
+
+    // called once an attack chosen with `make_attack` is over, with the
+    // outcome of all of its rounds
+    fn attack_resolved(&self, _attack: &Attack, _outcome: &AttackOutcome) {}
+
+    // called when the engine refuses the choice just made, before the player
+    // is asked again
+    fn action_rejected(&self, _reason: Rejection) {}
 }
 
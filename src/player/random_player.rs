@@ -1,10 +1,11 @@
-use rand::{self, Rng};
-use std::collections::HashMap;
+use rand::Rng;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 
 use super::Player;
 use ::{PlayerId, TerritoryId, NumArmies, CardAndId, AttackTerritories};
-use ::{GameBoard, GameMap, Trade, Reinforcement, Attack, Move};
-use ::attacking_allowed;
+use ::{GameBoard, Trade, Reinforcement, Attack, Move};
+use ::{attacking_allowed, seeded_rng, GameRng};
 
 pub struct RandomPlayer {
     // determines how often the player trades in a set when it's not necessary
@@ -14,31 +15,65 @@ pub struct RandomPlayer {
     // determines how often the player attacks from a territory capable of
     // attacking
     param_attack: f64,
+
+    // the `Player` methods take `&self`, so the RNG needs interior mutability
+    rng: RefCell<GameRng>,
 }
 
 impl RandomPlayer {
-    // returns a vector of random players (each player is a trait object)
-    pub fn make_random_players(number: usize) -> Vec<Box<Player>> {
+    // makes a player whose parameters and decisions are all drawn from `rng`
+    pub fn new(mut rng: GameRng) -> RandomPlayer {
+        RandomPlayer {
+            param_nnt: rng.gen_range(0., 1.),
+            param_attack: rng.gen_range(0., 1.),
+            rng: RefCell::new(rng),
+        }
+    }
+
+    pub fn from_seed(seed: u64) -> RandomPlayer {
+        RandomPlayer::new(seeded_rng(seed))
+    }
+
+    // returns a vector of random players (each player is a trait object).
+    // each player gets its own RNG, seeded from `rng`
+    pub fn make_random_players<R: Rng>(number: usize, rng: &mut R) -> Vec<Box<dyn Player>> {
         let mut players = vec![];
         for _ in 0..number {
-            let player = RandomPlayer {
-                param_nnt: rand::thread_rng().gen_range(0., 1.),
-                param_attack: rand::thread_rng().gen_range(0., 1.),
-            };
-
-            players.push(Box::new(player) as Box<Player>);
+            let player = RandomPlayer::from_seed(rng.gen());
+            players.push(Box::new(player) as Box<dyn Player>);
         }
         players
     }
 }
 
 impl Player for RandomPlayer {
-    fn make_trade(&self, cards: &[CardAndId], _other_reinf: NumArmies, necessary: bool) -> Option<Trade> {
+    fn claim_territory(&self,
+                       _player: PlayerId,
+                       unclaimed: &[TerritoryId],
+                       _board: &dyn GameBoard)
+                       -> TerritoryId {
+        let rand_idx = self.rng.borrow_mut().gen_range(0, unclaimed.len());
+        unclaimed[rand_idx]
+    }
+
+    fn place_army(&self, player: PlayerId, _remaining: NumArmies, board: &dyn GameBoard) -> TerritoryId {
+        let owned = board.get_owned_territories(player);
+        let rand_idx = self.rng.borrow_mut().gen_range(0, owned.len());
+        owned[rand_idx]
+    }
+
+    fn make_trade(&self,
+                  cards: &[CardAndId],
+                  _other_reinf: NumArmies,
+                  _next_set_value: Option<NumArmies>,
+                  necessary: bool)
+                  -> Option<Trade> {
         // if necessary or not necessary but a random roll exceeded k for some k in [0, 1]
         // then we make a trade. Identify all of the sets and pick one at
         // random.
 
-        let x = rand::thread_rng().gen_range(0., 1.);
+        let mut rng = self.rng.borrow_mut();
+        let x = rng.gen_range(0., 1.);
         if !necessary && x < self.param_nnt {
             return None;
         }
@@ -49,7 +84,7 @@ impl Player for RandomPlayer {
         for i in 0..n {
             card_idxs.push(i);
         }
-        rand::thread_rng().shuffle(&mut card_idxs);
+        rng.shuffle(&mut card_idxs);
 
         // exhaustively search all subsets of order 3 to see if one is a set
         for i in 0..(n - 2) {
@@ -72,12 +107,14 @@ impl Player for RandomPlayer {
 
     fn distrib_reinforcements(&self,
                               reinf: NumArmies,
-                              owned: &[TerritoryId])
+                              owned: &[TerritoryId],
+                              _board: &dyn GameBoard)
                               -> Reinforcement {
-        let mut terr_reinf = HashMap::new();
+        let mut rng = self.rng.borrow_mut();
+        let mut terr_reinf = BTreeMap::new();
         for _ in 0..reinf {
             // pick a random owned territory to assign this reinforcement to
-            let rand_idx = rand::thread_rng().gen_range(0, owned.len());
+            let rand_idx = rng.gen_range(0, owned.len());
             let rand_terr = owned[rand_idx];
             let amt = terr_reinf.entry(rand_terr).or_insert(0);
             *amt += 1;
@@ -86,16 +123,17 @@ impl Player for RandomPlayer {
         Reinforcement::new(terr_reinf)
     }
 
-    fn make_attack(&self, terr_info: &AttackTerritories) -> Option<Attack> {
+    fn make_attack(&self, terr_info: &AttackTerritories, _board: &dyn GameBoard) -> Option<Attack> {
+        let mut rng = self.rng.borrow_mut();
         for info in terr_info.values() {
-            if info.armies > 1 && info.adj_enemies.len() > 0 {
-                let x = rand::thread_rng().gen_range(0., 1.);
+            if info.armies > 1 && !info.adj_enemies.is_empty() {
+                let x = rng.gen_range(0., 1.);
                 if x >= self.param_attack {
                     let defender = {
                         let mut adj_enemies: Vec<_> = info.adj_enemies.iter()
-                                                                      .map(|&e| e)
+                                                                      .cloned()
                                                                       .collect();
-                        rand::thread_rng().shuffle(&mut adj_enemies);
+                        rng.shuffle(&mut adj_enemies);
                         adj_enemies[0]
                     };
 
@@ -110,11 +148,17 @@ impl Player for RandomPlayer {
         None
     }
 
-    fn make_combat_move(&self) -> Move {
-        unimplemented!()
+    fn make_combat_move(&self,
+                        _origin: TerritoryId,
+                        _target: TerritoryId,
+                        min: NumArmies,
+                        max: NumArmies,
+                        _board: &dyn GameBoard)
+                        -> NumArmies {
+        self.rng.borrow_mut().gen_range(min, max + 1)
     }
 
-    fn fortify(&self, player: PlayerId, board: &GameBoard) -> Option<Move> {
+    fn fortify(&self, player: PlayerId, board: &dyn GameBoard) -> Option<Move> {
         // generate a vector of (tid, list of owned territories adjacent to tid) items,
         // one for each territory owned by the player
         let mut terrs_w_adj_owned: Vec<_> = board.get_owned_territories(player)
@@ -127,34 +171,31 @@ impl Player for RandomPlayer {
            }).collect();
 
         // filter out territories with friendly neighbors
-        terrs_w_adj_owned = terrs_w_adj_owned.into_iter()
-                                             .filter(|&(_, ref owned)| owned.len() > 0)
-                                             .collect();
+        terrs_w_adj_owned.retain(|(_, owned)| !owned.is_empty());
 
         // filter out territories with only 1 army
-        terrs_w_adj_owned = terrs_w_adj_owned.into_iter()
-                                             .filter(|&(tid, _)| board.get_num_armies(tid) > 1)
-                                             .collect();
+        terrs_w_adj_owned.retain(|&(tid, _)| board.get_num_armies(tid) > 1);
 
-        if terrs_w_adj_owned.len() == 0 {
+        if terrs_w_adj_owned.is_empty() {
             return None;
         }
 
         // pick a random owned territory that has at least one adjacent owned
         // territory.
-        rand::thread_rng().shuffle(&mut terrs_w_adj_owned);
-        let mut origin = &mut terrs_w_adj_owned[0];
+        let mut rng = self.rng.borrow_mut();
+        rng.shuffle(&mut terrs_w_adj_owned);
+        let origin = &mut terrs_w_adj_owned[0];
 
         // pick a random destination territory
-        rand::thread_rng().shuffle(&mut origin.1);
+        rng.shuffle(&mut origin.1);
         let destination = origin.1[0];
 
 
         // pick a random int between 0 and get_num_armies(origin territory) - 1
-        let rand_num_armies = rand::thread_rng().gen_range(0, board.get_num_armies(origin.0) - 1);
+        let rand_num_armies = rng.gen_range(0, board.get_num_armies(origin.0) - 1);
         Some(Move {
             origin: origin.0,
-            destination: destination,
+            destination,
             amount: rand_num_armies,
         })
 
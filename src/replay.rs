@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+
+use board::{GameBoard, GameMap, StandardGameBoard, UNCLAIMED};
+use error::WolfriskError;
+use event::GameEvent;
+use map::{MapError, WorldMap};
+use super::{TerritoryId, NumArmies};
+
+#[derive(Debug)]
+pub enum ReplayError {
+    // the first event of a replay has to be `GameStarted`
+    MissingStart,
+    Map(MapError),
+    // the event at `index` can't have happened on the board as it was
+    Inconsistent { index: usize, reason: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::MissingStart => write!(f, "event log doesn't start with GameStarted"),
+            ReplayError::Map(ref err) => write!(f, "invalid map in event log: {}", err),
+            ReplayError::Inconsistent { index, ref reason } => {
+                write!(f, "event {} doesn't fit the board: {}", index, reason)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<MapError> for ReplayError {
+    fn from(err: MapError) -> ReplayError {
+        ReplayError::Map(err)
+    }
+}
+
+
+// Rebuilds the board of a recorded game one event at a time.
+pub struct Replay {
+    events: Vec<GameEvent>,
+    board: StandardGameBoard,
+    // index of the next event to apply
+    next: usize,
+}
+
+impl Replay {
+    // sets up the board described by the first event, which has to be
+    // `GameStarted`
+    pub fn new(events: Vec<GameEvent>) -> Result<Replay, ReplayError> {
+        let board = match events.first() {
+            Some(&GameEvent::GameStarted { ref map, num_players, neutral, ref territories }) => {
+                let map = WorldMap::from_definition(map)?;
+                let board_players = num_players + neutral.map_or(0, |_| 1);
+                StandardGameBoard::new(board_players, territories.clone(), map)
+                    .map_err(|err| ReplayError::Inconsistent { index: 0, reason: err.to_string() })?
+            }
+            _ => return Err(ReplayError::MissingStart),
+        };
+
+        Ok(Replay {
+            events,
+            board,
+            next: 1,
+        })
+    }
+
+    pub fn board(&self) -> &StandardGameBoard {
+        &self.board
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    // the number of events applied so far
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    // applies the next event to the board and returns it, or returns None if
+    // every event has been applied
+    pub fn step(&mut self) -> Result<Option<&GameEvent>, ReplayError> {
+        if self.is_finished() {
+            return Ok(None);
+        }
+
+        let index = self.next;
+        let event = self.events[index].clone();
+        self.apply(index, &event)?;
+        self.next += 1;
+        Ok(Some(&self.events[index]))
+    }
+
+    // applies all remaining events
+    pub fn run_to_end(&mut self) -> Result<(), ReplayError> {
+        while self.step()?.is_some() {}
+        Ok(())
+    }
+
+    fn apply(&mut self, index: usize, event: &GameEvent) -> Result<(), ReplayError> {
+        match *event {
+            GameEvent::GameStarted { .. } => {
+                return Err(ReplayError::Inconsistent {
+                    index,
+                    reason: String::from("a game can only start once"),
+                });
+            }
+            GameEvent::Trade { ref territory_bonuses, .. } => {
+                for &(terr, armies) in territory_bonuses.iter() {
+                    self.add(index, terr, armies)?;
+                }
+            }
+            GameEvent::TerritoryClaimed { player, territory } => {
+                self.check_territory(index, territory)?;
+                if self.board.get_owner(territory) != UNCLAIMED {
+                    return Err(ReplayError::Inconsistent {
+                        index,
+                        reason: format!("territory {} has been claimed already", territory),
+                    });
+                }
+                self.board.set_territory(territory, player, 1)
+                          .map_err(|err| inconsistent(index, err))?;
+            }
+            GameEvent::ArmyPlaced { territory, .. } => {
+                self.add(index, territory, 1)?;
+            }
+            GameEvent::Reinforced { territory, armies, .. } => {
+                self.add(index, territory, armies)?;
+            }
+            GameEvent::BattleResolved { origin, target, attacker_losses, defender_losses, .. } => {
+                self.remove(index, origin, attacker_losses)?;
+                self.remove(index, target, defender_losses)?;
+            }
+            GameEvent::TerritoryConquered { player, origin, target, armies, .. } => {
+                self.check_territory(index, target)?;
+                if self.board.get_num_armies(target) != 0 {
+                    return Err(ReplayError::Inconsistent {
+                        index,
+                        reason: format!("territory {} still has armies", target),
+                    });
+                }
+                self.remove(index, origin, armies)?;
+                self.board.set_territory(target, player, armies)
+                          .map_err(|err| inconsistent(index, err))?;
+            }
+            GameEvent::Fortified { origin, destination, armies, .. } => {
+                self.remove(index, origin, armies)?;
+                self.add(index, destination, armies)?;
+            }
+            GameEvent::TurnStarted { .. } |
+            GameEvent::PhaseChanged { .. } |
+            GameEvent::CardDrawn { .. } |
+            GameEvent::CardsCaptured { .. } |
+            GameEvent::BlitzResolved { .. } |
+            GameEvent::PlayerEliminated { .. } |
+            GameEvent::ActionRejected { .. } |
+            GameEvent::PlayerForfeited { .. } |
+            GameEvent::GameOver { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn check_territory(&self, index: usize, terr: TerritoryId) -> Result<(), ReplayError> {
+        if (terr as usize) < self.board.world_map().num_territories() {
+            Ok(())
+        } else {
+            Err(ReplayError::Inconsistent {
+                index,
+                reason: format!("there is no territory {}", terr),
+            })
+        }
+    }
+
+    fn add(&mut self, index: usize, terr: TerritoryId, armies: NumArmies) -> Result<(), ReplayError> {
+        self.board.add_armies(terr, armies).map_err(|err| inconsistent(index, err))
+    }
+
+    fn remove(&mut self, index: usize, terr: TerritoryId, armies: NumArmies) -> Result<(), ReplayError> {
+        self.board.remove_armies(terr, armies).map_err(|err| inconsistent(index, err))
+    }
+}
+
+fn inconsistent(index: usize, err: WolfriskError) -> ReplayError {
+    ReplayError::Inconsistent { index, reason: err.to_string() }
+}
@@ -0,0 +1,216 @@
+use std::cmp::max;
+
+use board::GameBoard;
+use super::{PlayerId, NumArmies, CardSymbol, Trade};
+
+// How much a set of cards is worth.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardValuation {
+    // every set is worth the same every time it is traded: three of a kind
+    // by symbol, or `mixed` for one of each. a wild stands in for whichever
+    // symbol makes the set worth the most
+    Fixed {
+        infantry: NumArmies,
+        cavalry: NumArmies,
+        artillery: NumArmies,
+        mixed: NumArmies,
+    },
+    // every set is worth the same, but the value goes up with every set
+    // traded by anyone in the game: the n-th set traded is worth `values[n]`,
+    // and every set after the last of `values` is worth `increment` more than
+    // the one before
+    Progressive {
+        values: Vec<NumArmies>,
+        increment: NumArmies,
+    },
+}
+
+// Where a player can move armies at the end of their turn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FortifyRule {
+    // one move, between neighboring territories
+    Adjacent,
+    // one move, along any path through the player's own territories
+    Connected,
+    // up to this many moves, each between neighboring territories
+    Moves(usize),
+}
+
+// The rules a game is played by. Everything the engine awards or limits that
+// varies between rule sets lives here; `Rules::default()` is the classic game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    pub card_valuation: CardValuation,
+
+    // a player never gets fewer reinforcements for their territories than this
+    pub min_reinforcement: NumArmies,
+
+    // a player gets one reinforcement for every this many territories
+    pub territories_per_army: NumArmies,
+
+    // the armies awarded for holding a whole continent, by continent index.
+    // None uses the bonuses listed on the map
+    pub continent_bonuses: Option<Vec<NumArmies>>,
+
+    // a player holding at least this many cards has to trade at the start of
+    // their turn
+    pub forced_trade_hand_size: usize,
+
+    // a player who takes the cards of a player they eliminated and then holds
+    // at least this many has to trade right away, until they hold fewer than
+    // `forced_trade_hand_size`
+    pub elimination_trade_hand_size: usize,
+
+    pub fortify: FortifyRule,
+
+    // the game ends without a winner after this many turns. None means no limit
+    pub turn_limit: Option<usize>,
+
+    // extra armies placed on each traded card's territory, if the player
+    // trading owns it
+    pub owned_territory_bonus: NumArmies,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::classic()
+    }
+}
+
+impl Rules {
+    // fixed set values of 4, 6 and 8 by symbol and 10 for a mixed set, and a
+    // limit of 100 turns
+    pub fn classic() -> Rules {
+        Rules {
+            card_valuation: CardValuation::Fixed {
+                infantry: 4,
+                cavalry: 6,
+                artillery: 8,
+                mixed: 10,
+            },
+            min_reinforcement: 3,
+            territories_per_army: 3,
+            continent_bonuses: None,
+            forced_trade_hand_size: 5,
+            elimination_trade_hand_size: 6,
+            fortify: FortifyRule::Adjacent,
+            turn_limit: Some(100),
+            owned_territory_bonus: 2,
+        }
+    }
+
+    // the classic rules, played until somebody wins
+    pub fn unlimited() -> Rules {
+        Rules { turn_limit: None, ..Rules::classic() }
+    }
+
+    // the classic rules with set values going up through 4, 6, 8, 10, 12 and
+    // 15, and by 5 for every set after that
+    pub fn progressive() -> Rules {
+        Rules {
+            card_valuation: CardValuation::Progressive {
+                values: vec![4, 6, 8, 10, 12, 15],
+                increment: 5,
+            },
+            ..Rules::classic()
+        }
+    }
+
+    // the classic rules, with fortification along any path of the player's
+    // own territories
+    pub fn connected_fortify() -> Rules {
+        Rules { fortify: FortifyRule::Connected, ..Rules::classic() }
+    }
+
+    // the classic rules without continent bonuses
+    pub fn no_continent_bonuses() -> Rules {
+        Rules { continent_bonuses: Some(Vec::new()), ..Rules::classic() }
+    }
+
+    // looks up a preset by the name used on the command line
+    pub fn preset(name: &str) -> Option<Rules> {
+        match name {
+            "classic" => Some(Rules::classic()),
+            "unlimited" => Some(Rules::unlimited()),
+            "progressive" => Some(Rules::progressive()),
+            "connected-fortify" => Some(Rules::connected_fortify()),
+            "no-continent-bonuses" => Some(Rules::no_continent_bonuses()),
+            _ => None,
+        }
+    }
+
+    // the value of the next set traded, after `trades` sets have been traded
+    // in the game so far. None if the value depends on the cards in the set
+    pub fn next_set_value(&self, trades: usize) -> Option<NumArmies> {
+        match self.card_valuation {
+            CardValuation::Fixed { .. } => None,
+            CardValuation::Progressive { ref values, increment } => {
+                match values.get(trades) {
+                    Some(&value) => Some(value),
+                    None => {
+                        let last = values.last().cloned().unwrap_or(0);
+                        let extra = (trades + 1 - values.len()) as NumArmies;
+                        Some(last.saturating_add(increment.saturating_mul(extra)))
+                    }
+                }
+            }
+        }
+    }
+
+    // the number of armies `trade` is worth, or 0 if it isn't a set, after
+    // `trades` sets have been traded in the game so far
+    pub fn set_value(&self, trade: &Trade, trades: usize) -> NumArmies {
+        if !trade.is_set() {
+            return 0;
+        }
+
+        match self.card_valuation {
+            CardValuation::Progressive { .. } => self.next_set_value(trades).unwrap_or(0),
+            CardValuation::Fixed { infantry, cavalry, artillery, mixed } => {
+                let value_of = |symbol| match symbol {
+                    CardSymbol::Infantry => infantry,
+                    CardSymbol::Cavalry => cavalry,
+                    CardSymbol::Artillery => artillery,
+                };
+
+                let symbols: Vec<_> = trade.cards
+                                           .iter()
+                                           .filter_map(|&(card, _)| card.get_symbol())
+                                           .collect();
+                match symbols.len() {
+                    3 if symbols[0] == symbols[1] => value_of(symbols[0]),
+                    3 => mixed,
+                    // one wild: it has to match the other two if they match,
+                    // and be the missing symbol if they don't
+                    2 if symbols[0] == symbols[1] => value_of(symbols[0]),
+                    2 => mixed,
+                    // two or three wilds can make any set
+                    1 => max(mixed, value_of(symbols[0])),
+                    _ => max(mixed, max(infantry, max(cavalry, artillery))),
+                }
+            }
+        }
+    }
+
+    // the reinforcements for holding `num_territories` territories: one for
+    // every `territories_per_army`, rounded down, but at least
+    // `min_reinforcement`
+    pub fn territory_reinforcements(&self, num_territories: NumArmies) -> NumArmies {
+        max(num_territories / max(self.territories_per_army, 1), self.min_reinforcement)
+    }
+
+    // the reinforcements `player` gets for the continents they hold
+    pub fn continent_reinforcements(&self, board: &dyn GameBoard, player: PlayerId) -> NumArmies {
+        match self.continent_bonuses {
+            None => board.get_continent_bonuses(player),
+            Some(ref bonuses) => {
+                board.continents()
+                     .iter()
+                     .enumerate()
+                     .filter(|&(_, continent)| board.player_owns_continent(player, continent))
+                     .map(|(i, _)| bonuses.get(i).cloned().unwrap_or(0))
+                     .fold(0, |total: NumArmies, bonus| total.saturating_add(bonus))
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bincode;
+use serde_json;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use game_manager::CardManager;
+use map::{MapDefinition, MapError};
+use rules::Rules;
+use super::{PlayerId, NumArmies};
+
+// Bumped whenever the layout of `GameSnapshot` changes. Snapshots with a
+// different version are refused rather than misread.
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+// binary snapshots start with these bytes, followed by the version as a
+// little-endian u32 and then the bincode-encoded snapshot
+const BINARY_MAGIC: &[u8; 4] = b"WRSK";
+
+// The complete state of a game between two turns: the map, the rules, the
+// board, the cards, whose turn is next and how many turns have been played. Players are
+// not part of a snapshot, they are supplied again when the game is restored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub version: u32,
+    pub map: MapDefinition,
+    pub rules: Rules,
+    // the number of real players. the neutral player, if there is one, has
+    // the ID `num_players`
+    pub num_players: PlayerId,
+    pub neutral: Option<PlayerId>,
+    // the owner and number of armies of each territory, indexed by territory ID
+    pub territories: Vec<(PlayerId, NumArmies)>,
+    pub cards: CardManager,
+    // the number of sets traded in the game so far
+    pub trades: usize,
+    pub curr_player: PlayerId,
+    pub turn: usize,
+    // players who have forfeited and no longer take turns
+    pub forfeited: Vec<PlayerId>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Binary,
+}
+
+impl SnapshotFormat {
+    // JSON for paths ending in ".json", binary for anything else
+    pub fn for_path<P: AsRef<Path>>(path: P) -> SnapshotFormat {
+        match path.as_ref().extension() {
+            Some(ext) if ext == "json" => SnapshotFormat::Json,
+            _ => SnapshotFormat::Binary,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    UnsupportedVersion(u32),
+    // the data is neither a JSON nor a binary snapshot
+    NotASnapshot,
+    // the snapshot's map couldn't be loaded
+    Map(MapError),
+    // the parts of the snapshot don't agree with each other
+    Inconsistent(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Io(ref err) => write!(f, "couldn't access snapshot: {}", err),
+            SnapshotError::Json(ref err) => write!(f, "invalid JSON snapshot: {}", err),
+            SnapshotError::Binary(ref err) => write!(f, "invalid binary snapshot: {}", err),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "snapshot version {} is not supported (expected {})",
+                       v,
+                       SNAPSHOT_VERSION)
+            }
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot"),
+            SnapshotError::Map(ref err) => write!(f, "invalid map in snapshot: {}", err),
+            SnapshotError::Inconsistent(ref msg) => write!(f, "inconsistent snapshot: {}", msg),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> SnapshotError {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> SnapshotError {
+        SnapshotError::Json(err)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> SnapshotError {
+        SnapshotError::Binary(err)
+    }
+}
+
+impl From<MapError> for SnapshotError {
+    fn from(err: MapError) -> SnapshotError {
+        SnapshotError::Map(err)
+    }
+}
+
+// used to check the version of a JSON snapshot before reading the rest of it
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl GameSnapshot {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("A GameSnapshot can always be serialized")
+    }
+
+    pub fn from_json(json: &str) -> Result<GameSnapshot, SnapshotError> {
+        let version: Version = serde_json::from_str(json)?;
+        if version.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version.version));
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend(bincode::serialize(self).expect("A GameSnapshot can always be serialized"));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<GameSnapshot, SnapshotError> {
+        if !is_binary(bytes) || bytes.len() < 8 {
+            return Err(SnapshotError::NotASnapshot);
+        }
+
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        Ok(bincode::deserialize(&bytes[8..])?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: SnapshotFormat) -> Result<(), SnapshotError> {
+        let bytes = match format {
+            SnapshotFormat::Json => self.to_json().into_bytes(),
+            SnapshotFormat::Binary => self.to_bytes(),
+        };
+        File::create(path)?.write_all(&bytes)?;
+        Ok(())
+    }
+
+    // reads a snapshot in either format
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameSnapshot, SnapshotError> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        if is_binary(&bytes) {
+            GameSnapshot::from_bytes(&bytes)
+        } else {
+            let json = String::from_utf8(bytes).map_err(|_| SnapshotError::NotASnapshot)?;
+            GameSnapshot::from_json(&json)
+        }
+    }
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(BINARY_MAGIC)
+}
@@ -0,0 +1,562 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use board::{GameBoard, GameMap};
+use combat::{CombatResolver, DiceResolver};
+use error::{Rejection, WolfriskError};
+use game_manager::CardManager;
+use legal::{self, AttackOption, CombatMoveRange, FortifyOption};
+use map::{Continent, MapDefinition, MapError, WorldMap};
+use rules::{FortifyRule, Rules};
+use super::{PlayerId, TerritoryId, NumArmies, Card, CardAndId, CardId, GameRng, Trade, Move};
+use super::defending_allowed;
+
+// Where a `GameState` is in the current player's turn, which decides what
+// they can do next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TurnStage {
+    // trading in sets, which so far have brought in `armies`. in the middle
+    // of an attack, after taking an eliminated player's cards, the player
+    // trades until they hold fewer than `forced_trade_hand_size`, places the
+    // armies and then goes on attacking
+    Trade { armies: NumArmies, mid_attack: bool },
+    // `armies` are still to be placed
+    Reinforce { armies: NumArmies, mid_attack: bool },
+    Attack,
+    // `target` has just been emptied by an attack from `origin`, and between
+    // `min` and `max` armies have to move in
+    CombatMove { origin: TerritoryId, target: TerritoryId, min: NumArmies, max: NumArmies },
+    // up to `moves_left` more armies moves before the turn ends
+    Fortify { moves_left: usize },
+    // somebody won, or the turn limit was reached
+    GameOver,
+}
+
+// One step the current player can take in a `GameState`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    // trade in the cards with these IDs
+    Trade([CardId; 3]),
+    // stop trading and go on to placing reinforcements
+    EndTrade,
+    // place some of the reinforcements
+    Reinforce { territory: TerritoryId, armies: NumArmies },
+    // fight one round of dice with `dice` attacking armies
+    Attack { origin: TerritoryId, target: TerritoryId, dice: NumArmies },
+    // move this many armies into a conquered territory
+    CombatMove(NumArmies),
+    EndAttack,
+    Fortify { origin: TerritoryId, destination: TerritoryId, armies: NumArmies },
+    EndTurn,
+}
+
+// The whole state of a game between players' decisions, cheap to clone and
+// stepped forward one `Action` at a time, so that computer players can look
+// ahead. It plays by the same rules as `GameManager`, except that every
+// battle is decided with `DiceResolver`. Owners from `num_players` on are
+// neutral: they hold territories but never take a turn or hold cards.
+#[derive(Clone)]
+pub struct GameState {
+    map: Rc<WorldMap>,
+    rules: Rc<Rules>,
+    territories: Vec<(PlayerId, NumArmies)>,
+    cards: CardManager,
+    trades: usize,
+    forfeited: BTreeSet<PlayerId>,
+    curr_player: PlayerId,
+    turn: usize,
+    stage: TurnStage,
+    // whether the current player has conquered a territory this turn, and
+    // so draws a card at the end of their attacks
+    conquered: bool,
+}
+
+impl GameState {
+    // the state of a game with the territories of `board`, about to start
+    // the first turn of player 0. the players are those `cards` has hands for
+    pub fn new(board: &dyn GameBoard, rules: Rules, cards: CardManager) -> Result<GameState, MapError> {
+        let map = WorldMap::from_definition(&MapDefinition::from_map(board.game_map(), board.continents()))?;
+        let territories = (0..map.num_territories())
+                              .map(|i| {
+                                  let tid = i as TerritoryId;
+                                  (board.get_owner(tid), board.get_num_armies(tid))
+                              })
+                              .collect();
+
+        let mut state = GameState {
+            map: Rc::new(map),
+            rules: Rc::new(rules),
+            territories,
+            cards,
+            trades: 0,
+            forfeited: BTreeSet::new(),
+            curr_player: 0,
+            turn: 0,
+            stage: TurnStage::GameOver,
+            conquered: false,
+        };
+        state = state.starting_turn(0, 0);
+        Ok(state)
+    }
+
+    // the same state, with `trades` sets traded in the game so far
+    pub fn with_trades(mut self, trades: usize) -> GameState {
+        self.trades = trades;
+        self
+    }
+
+    // the same state, where the `forfeited` players no longer take turns
+    pub fn with_forfeited(mut self, forfeited: BTreeSet<PlayerId>) -> GameState {
+        self.forfeited = forfeited;
+        self
+    }
+
+    // the same board and cards, with `turn` turns played and `player` (or the
+    // next player still in the game) about to start theirs
+    pub fn starting_turn(mut self, player: PlayerId, turn: usize) -> GameState {
+        self.turn = turn;
+        self.conquered = false;
+        if self.is_over() {
+            self.stage = TurnStage::GameOver;
+            return self;
+        }
+
+        self.curr_player = player;
+        if !self.is_active(player) {
+            self.curr_player = self.next_active_player();
+        }
+        self.start_turn();
+        self
+    }
+
+    // the same board and cards, with `player` part of the way through their
+    // turn
+    pub fn at_stage(mut self, player: PlayerId, stage: TurnStage) -> GameState {
+        self.curr_player = player;
+        self.stage = stage;
+        self
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    // the number of players taking turns, not counting any neutral player
+    pub fn num_players(&self) -> usize {
+        self.cards.num_players()
+    }
+
+    pub fn current_player(&self) -> PlayerId {
+        self.curr_player
+    }
+
+    pub fn stage(&self) -> TurnStage {
+        self.stage
+    }
+
+    // the number of turns started so far
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn trades(&self) -> usize {
+        self.trades
+    }
+
+    pub fn hand(&self, player: PlayerId) -> Vec<CardAndId> {
+        self.cards.get_player_cards(player).unwrap_or_default()
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.stage == TurnStage::GameOver
+    }
+
+    // the winner of a game that is over, if it didn't end on the turn limit
+    pub fn winner(&self) -> Option<PlayerId> {
+        let active = self.active_players();
+        if self.is_game_over() && active.len() == 1 {
+            Some(active[0])
+        } else {
+            None
+        }
+    }
+
+    // the sets the current player can trade, if they are trading
+    pub fn legal_trades(&self) -> Vec<Trade> {
+        match self.stage {
+            TurnStage::Trade { .. } => legal::legal_trades(&self.hand(self.curr_player)),
+            _ => Vec::new(),
+        }
+    }
+
+    // the attacks the current player can make, if they are attacking
+    pub fn attack_options(&self) -> Vec<AttackOption> {
+        match self.stage {
+            TurnStage::Attack => legal::attack_options(self, self.curr_player),
+            _ => Vec::new(),
+        }
+    }
+
+    // the armies the current player can move into the territory they just
+    // conquered, if they have to
+    pub fn combat_move_range(&self) -> Option<CombatMoveRange> {
+        match self.stage {
+            TurnStage::CombatMove { min, max, .. } => Some(CombatMoveRange { min, max }),
+            _ => None,
+        }
+    }
+
+    // the fortifications the current player can make, if they are fortifying
+    pub fn fortify_options(&self) -> Vec<FortifyOption> {
+        match self.stage {
+            TurnStage::Fortify { .. } => legal::fortify_options(self, &self.rules, self.curr_player),
+            _ => Vec::new(),
+        }
+    }
+
+    // every action the current player can take. attacks come with every
+    // number of dice, but reinforcements are only offered all on one
+    // territory, combat moves only as the fewest or the most armies allowed,
+    // and fortification only with all the armies that can move. `apply`
+    // accepts anything the rules allow
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        match self.stage {
+            TurnStage::Trade { mid_attack, .. } => {
+                for trade in self.legal_trades() {
+                    actions.push(Action::Trade([trade.cards[0].1, trade.cards[1].1, trade.cards[2].1]));
+                }
+                if !mid_attack && self.hand(self.curr_player).len() < self.rules.forced_trade_hand_size {
+                    actions.push(Action::EndTrade);
+                }
+            }
+            TurnStage::Reinforce { armies, .. } => {
+                for territory in self.get_owned_territories(self.curr_player) {
+                    actions.push(Action::Reinforce { territory, armies });
+                }
+            }
+            TurnStage::Attack => {
+                for option in self.attack_options() {
+                    for dice in 1..(option.max_dice + 1) {
+                        actions.push(Action::Attack { origin: option.origin, target: option.target, dice });
+                    }
+                }
+                actions.push(Action::EndAttack);
+            }
+            TurnStage::CombatMove { min, max, .. } => {
+                actions.push(Action::CombatMove(min));
+                if max > min {
+                    actions.push(Action::CombatMove(max));
+                }
+            }
+            TurnStage::Fortify { .. } => {
+                for option in self.fortify_options() {
+                    actions.push(Action::Fortify {
+                        origin: option.origin,
+                        destination: option.destination,
+                        armies: option.max_armies,
+                    });
+                }
+                actions.push(Action::EndTurn);
+            }
+            TurnStage::GameOver => {}
+        }
+        actions
+    }
+
+    // takes `action` for the current player and moves the game on to the
+    // next decision, drawing dice and cards from `rng`. an action that
+    // doesn't fit the state is rejected, leaving the state as it was
+    pub fn apply(&mut self, action: Action, rng: &mut GameRng) -> Result<(), Rejection> {
+        let player = self.curr_player;
+        match (self.stage, action) {
+            (TurnStage::Trade { armies, mid_attack }, Action::Trade(ids)) => {
+                // a card that isn't held stands in as a wild, which
+                // `check_trade` rejects as not held
+                let hand = self.hand(player);
+                let mut cards = [(Card::Wild, 0); 3];
+                for (card, &cid) in cards.iter_mut().zip(ids.iter()) {
+                    *card = hand.iter().find(|&&(_, id)| id == cid).cloned().unwrap_or((Card::Wild, cid));
+                }
+                let trade = Trade::new(cards);
+                legal::check_trade(&hand, &trade)?;
+
+                let armies = armies.saturating_add(self.perform_trade(player, &trade));
+                self.stage = TurnStage::Trade { armies, mid_attack };
+                self.continue_trading();
+            }
+            (TurnStage::Trade { armies, mid_attack: false }, Action::EndTrade) => {
+                if self.hand(player).len() >= self.rules.forced_trade_hand_size {
+                    return Err(Rejection::TradeRequired);
+                }
+                self.finish_trading(armies, false);
+            }
+            (TurnStage::Reinforce { armies: remaining, mid_attack }, Action::Reinforce { territory, armies }) => {
+                legal::check_owner(self, player, territory)?;
+                if armies == 0 {
+                    return Err(Rejection::TooFewArmies { required: 1, requested: armies });
+                }
+                if armies > remaining {
+                    return Err(Rejection::TooManyArmies { allowed: remaining, requested: armies });
+                }
+
+                self.add_to(territory, armies);
+                self.stage = if armies == remaining {
+                    TurnStage::Attack
+                } else {
+                    TurnStage::Reinforce { armies: remaining - armies, mid_attack }
+                };
+            }
+            (TurnStage::Attack, Action::Attack { origin, target, dice }) => {
+                legal::check_attack(self, player, origin, target, dice)?;
+                self.perform_battle(origin, target, dice, rng)?;
+            }
+            (TurnStage::Attack, Action::EndAttack) => {
+                if self.conquered {
+                    // with no cards left to draw, the player just goes without
+                    let _ = self.cards.draw_random_for_player(player, rng);
+                }
+                let moves_left = match self.rules.fortify {
+                    FortifyRule::Adjacent | FortifyRule::Connected => 1,
+                    FortifyRule::Moves(moves) => moves,
+                };
+                if moves_left == 0 {
+                    self.end_turn();
+                } else {
+                    self.stage = TurnStage::Fortify { moves_left };
+                }
+            }
+            (TurnStage::CombatMove { origin, target, min, max }, Action::CombatMove(armies)) => {
+                legal::check_combat_move(CombatMoveRange { min, max }, armies)?;
+                self.conquer(origin, target, armies);
+            }
+            (TurnStage::Fortify { moves_left }, Action::Fortify { origin, destination, armies }) => {
+                let rules = self.rules.clone();
+                legal::check_fortify(self, &rules, player, &Move { origin, destination, amount: armies })?;
+                self.territories[origin as usize].1 -= armies;
+                self.add_to(destination, armies);
+                if moves_left > 1 {
+                    self.stage = TurnStage::Fortify { moves_left: moves_left - 1 };
+                } else {
+                    self.end_turn();
+                }
+            }
+            (TurnStage::Fortify { .. }, Action::EndTurn) => self.end_turn(),
+            _ => return Err(Rejection::WrongStage),
+        }
+        Ok(())
+    }
+
+    // whether `player` still takes turns
+    fn is_active(&self, player: PlayerId) -> bool {
+        !self.player_is_defeated(player) && !self.forfeited.contains(&player)
+    }
+
+    fn active_players(&self) -> Vec<PlayerId> {
+        (0..self.num_players() as PlayerId).filter(|&p| self.is_active(p)).collect()
+    }
+
+    fn is_over(&self) -> bool {
+        self.game_is_over() || self.active_players().len() <= 1
+    }
+
+    // the first player after the current one who is still in the game
+    fn next_active_player(&self) -> PlayerId {
+        let num_players = self.num_players() as PlayerId;
+        let mut player = self.curr_player;
+        for _ in 0..num_players {
+            player = (player + 1) % num_players;
+            if self.is_active(player) {
+                break;
+            }
+        }
+        player
+    }
+
+    fn start_turn(&mut self) {
+        self.turn += 1;
+        self.conquered = false;
+        self.stage = TurnStage::Trade { armies: 0, mid_attack: false };
+        self.continue_trading();
+    }
+
+    fn end_turn(&mut self) {
+        self.conquered = false;
+        if self.is_over() || self.rules.turn_limit.is_some_and(|limit| self.turn >= limit) {
+            self.stage = TurnStage::GameOver;
+            return;
+        }
+        self.curr_player = self.next_active_player();
+        self.start_turn();
+    }
+
+    // moves on from trading once the player has no more sets to trade, or
+    // in the middle of an attack doesn't have to trade any more
+    fn continue_trading(&mut self) {
+        if let TurnStage::Trade { armies, mid_attack } = self.stage {
+            let hand = self.hand(self.curr_player).len();
+            if hand < 3 || (mid_attack && hand < self.rules.forced_trade_hand_size) {
+                self.finish_trading(armies, mid_attack);
+            }
+        }
+    }
+
+    fn finish_trading(&mut self, armies: NumArmies, mid_attack: bool) {
+        let armies = if mid_attack {
+            armies
+        } else {
+            let rules = self.rules.clone();
+            armies.saturating_add(self.get_territory_reinforcements(self.curr_player, &rules))
+        };
+        self.stage = if armies == 0 {
+            TurnStage::Attack
+        } else {
+            TurnStage::Reinforce { armies, mid_attack }
+        };
+    }
+
+    // discards the cards of `trade`, places the bonus armies for any of
+    // `player`'s territories on them and returns what the set is worth
+    fn perform_trade(&mut self, player: PlayerId, trade: &Trade) -> NumArmies {
+        for &(card, cid) in trade.cards.iter() {
+            let _ = self.cards.player_discard_card(player, cid);
+            if let Some(tid) = card.get_territory() {
+                if self.get_owner(tid) == player {
+                    let bonus = self.rules.owned_territory_bonus;
+                    self.add_to(tid, bonus);
+                }
+            }
+        }
+
+        let armies = self.rules.set_value(trade, self.trades);
+        self.trades += 1;
+        armies
+    }
+
+    fn perform_battle(&mut self,
+                      origin: TerritoryId,
+                      target: TerritoryId,
+                      dice: NumArmies,
+                      rng: &mut GameRng)
+                      -> Result<(), Rejection> {
+        let defending = defending_allowed(self.get_num_armies(target));
+        // the attack has been verified, and the target of one always has an
+        // army to defend with
+        let outcome = DiceResolver.resolve(dice, defending, rng).map_err(|_| Rejection::NoArmies)?;
+
+        self.territories[origin as usize].1 -= outcome.attacker_losses;
+        self.territories[target as usize].1 -= outcome.defender_losses;
+        if self.get_num_armies(target) > 0 {
+            return Ok(());
+        }
+
+        let range = legal::combat_move_range(self, origin, dice - outcome.attacker_losses);
+        if range.min >= range.max {
+            self.conquer(origin, target, range.min);
+        } else {
+            self.stage = TurnStage::CombatMove { origin, target, min: range.min, max: range.max };
+        }
+        Ok(())
+    }
+
+    // the current player moves `armies` from `origin` into the emptied
+    // `target`, taking the cards of its owner if that eliminates them
+    fn conquer(&mut self, origin: TerritoryId, target: TerritoryId, armies: NumArmies) {
+        let player = self.curr_player;
+        let defender = self.get_owner(target);
+        self.territories[origin as usize].1 -= armies;
+        self.territories[target as usize] = (player, armies);
+        self.conquered = true;
+        self.stage = TurnStage::Attack;
+
+        if !self.player_is_defeated(defender) {
+            return;
+        }
+        // a neutral player holds no cards
+        if (defender as usize) < self.num_players() {
+            let _ = self.cards.take_cards(defender, player);
+        }
+        if self.is_over() {
+            self.stage = TurnStage::GameOver;
+        } else if self.hand(player).len() >= self.rules.elimination_trade_hand_size {
+            self.stage = TurnStage::Trade { armies: 0, mid_attack: true };
+            self.continue_trading();
+        }
+    }
+
+    fn add_to(&mut self, terr: TerritoryId, armies: NumArmies) {
+        let territory = &mut self.territories[terr as usize];
+        territory.1 = territory.1.saturating_add(armies);
+    }
+}
+
+impl GameBoard for GameState {
+    fn get_owner(&self, terr: TerritoryId) -> PlayerId {
+        self.territories[terr as usize].0
+    }
+
+    fn get_num_armies(&self, terr: TerritoryId) -> NumArmies {
+        self.territories[terr as usize].1
+    }
+
+    fn get_num_owned_territories(&self, player: PlayerId) -> u8 {
+        self.territories.iter().filter(|&&(owner, _)| owner == player).count() as u8
+    }
+
+    fn get_owned_territories(&self, player: PlayerId) -> Vec<TerritoryId> {
+        (0..self.territories.len())
+            .filter(|&i| self.territories[i].0 == player)
+            .map(|i| i as TerritoryId)
+            .collect()
+    }
+
+    fn get_continent_bonuses(&self, player: PlayerId) -> NumArmies {
+        self.map
+            .continents()
+            .iter()
+            .filter(|continent| self.player_owns_continent(player, continent))
+            .map(|continent| continent.bonus as NumArmies)
+            .fold(0, |total: NumArmies, bonus| total.saturating_add(bonus))
+    }
+
+    fn player_owns_continent(&self, player: PlayerId, continent: &Continent) -> bool {
+        continent.territories.iter().all(|&terr| self.get_owner(terr) == player)
+    }
+
+    fn get_territory_reinforcements(&self, player: PlayerId, rules: &Rules) -> NumArmies {
+        let num_terr = self.get_num_owned_territories(player) as NumArmies;
+        let continents = rules.continent_reinforcements(self, player);
+        rules.territory_reinforcements(num_terr).saturating_add(continents)
+    }
+
+    fn set_territory(&mut self,
+                     terr: TerritoryId,
+                     owner: PlayerId,
+                     num_armies: NumArmies)
+                     -> Result<(), WolfriskError> {
+        match self.territories.get_mut(terr as usize) {
+            None => Err(WolfriskError::InvalidTerritory(terr)),
+            Some(territory) => {
+                *territory = (owner, num_armies);
+                Ok(())
+            }
+        }
+    }
+
+    fn game_is_over(&self) -> bool {
+        let owner0 = self.territories[0].0;
+        self.territories.iter().all(|&(owner, _)| owner == owner0)
+    }
+
+    fn player_is_defeated(&self, player: PlayerId) -> bool {
+        self.territories.iter().all(|&(owner, _)| owner != player)
+    }
+
+    fn game_map(&self) -> &dyn GameMap {
+        self.map.as_ref()
+    }
+
+    fn continents(&self) -> &[Continent] {
+        self.map.continents()
+    }
+}
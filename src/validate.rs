@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use board::GameMap;
+use map::Continent;
+use super::TerritoryId;
+
+// A problem found in a map. Continents are referred to by their index in the
+// continent table that was validated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapProblem {
+    // `from` lists `to` as a neighbor, but `to` doesn't list `from`
+    AsymmetricLink { from: TerritoryId, to: TerritoryId },
+    // `from` lists `to` as a neighbor `count` times
+    DuplicateEdge { from: TerritoryId, to: TerritoryId, count: usize },
+    // a territory lists itself as a neighbor
+    SelfLoop(TerritoryId),
+    // the map falls apart into several pieces that can't reach each other.
+    // holds the territories of every piece
+    Disconnected(Vec<Vec<TerritoryId>>),
+    NoContinent(TerritoryId),
+    // a territory belongs to more than one continent
+    OverlappingContinents { territory: TerritoryId, continents: Vec<usize> },
+    EmptyContinent(usize),
+    // a continent contains a territory that isn't on the map
+    UnknownTerritory { continent: usize, territory: TerritoryId },
+    // a continent lists the same territory more than once
+    DuplicateMember { continent: usize, territory: TerritoryId },
+}
+
+impl MapProblem {
+    // describes the problem, naming territories with `name` and continents
+    // with `continent`
+    pub fn describe(&self,
+                    name: &dyn Fn(TerritoryId) -> String,
+                    continent: &dyn Fn(usize) -> String)
+                    -> String {
+        let names = |terrs: &[TerritoryId]| {
+            terrs.iter().map(|&t| name(t)).collect::<Vec<_>>().join(", ")
+        };
+
+        match *self {
+            MapProblem::AsymmetricLink { from, to } => {
+                format!("{} lists {} as a neighbor, but not the other way around",
+                        name(from),
+                        name(to))
+            }
+            MapProblem::DuplicateEdge { from, to, count } => {
+                format!("{} lists {} as a neighbor {} times", name(from), name(to), count)
+            }
+            MapProblem::SelfLoop(terr) => format!("{} lists itself as a neighbor", name(terr)),
+            MapProblem::Disconnected(ref components) => {
+                let pieces: Vec<_> = components.iter()
+                                               .map(|c| format!("[{}]", names(c)))
+                                               .collect();
+                format!("map is split into {} unconnected pieces: {}",
+                        components.len(),
+                        pieces.join(" "))
+            }
+            MapProblem::NoContinent(terr) => format!("{} doesn't belong to a continent", name(terr)),
+            MapProblem::OverlappingContinents { territory, ref continents } => {
+                let conts: Vec<_> = continents.iter().map(|&c| continent(c)).collect();
+                format!("{} belongs to {} continents ({})",
+                        name(territory),
+                        continents.len(),
+                        conts.join(", "))
+            }
+            MapProblem::EmptyContinent(cont) => format!("{} has no territories", continent(cont)),
+            MapProblem::UnknownTerritory { continent: cont, territory } => {
+                format!("{} contains territory {}, which isn't on the map",
+                        continent(cont),
+                        territory)
+            }
+            MapProblem::DuplicateMember { continent: cont, territory } => {
+                format!("{} lists {} more than once", continent(cont), name(territory))
+            }
+        }
+    }
+}
+
+impl fmt::Display for MapProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{}",
+               self.describe(&|t| format!("territory {}", t), &|c| format!("continent {}", c)))
+    }
+}
+
+
+// Checks that `map` is a sensible map to play on with the given continents:
+// every border is listed exactly once from each side, every territory can be
+// reached from every other one, and every territory belongs to exactly one
+// continent. Returns all the problems found, so an empty vector means the map
+// is valid.
+pub fn validate_map(map: &dyn GameMap, continents: &[Continent]) -> Vec<MapProblem> {
+    let mut problems = Vec::new();
+    let n = map.num_territories();
+
+    for a in 0..n {
+        let a = a as TerritoryId;
+        let mut counts = BTreeMap::new();
+        for b in map.get_neighbors(a) {
+            *counts.entry(b).or_insert(0) += 1;
+        }
+
+        for (&b, &count) in counts.iter() {
+            if a == b {
+                problems.push(MapProblem::SelfLoop(a));
+                continue;
+            }
+            if count > 1 {
+                problems.push(MapProblem::DuplicateEdge { from: a, to: b, count });
+            }
+            if !map.are_adjacent(b, a) {
+                problems.push(MapProblem::AsymmetricLink { from: a, to: b });
+            }
+        }
+    }
+
+    let components = connected_components(map);
+    if components.len() > 1 {
+        problems.push(MapProblem::Disconnected(components));
+    }
+
+    let mut membership = vec![Vec::new(); n];
+    for (i, continent) in continents.iter().enumerate() {
+        if continent.territories.is_empty() {
+            problems.push(MapProblem::EmptyContinent(i));
+        }
+        for &terr in continent.territories.iter() {
+            if terr as usize >= n {
+                problems.push(MapProblem::UnknownTerritory { continent: i, territory: terr });
+            } else if membership[terr as usize].contains(&i) {
+                problems.push(MapProblem::DuplicateMember { continent: i, territory: terr });
+            } else {
+                membership[terr as usize].push(i);
+            }
+        }
+    }
+
+    for (terr, conts) in membership.into_iter().enumerate() {
+        let terr = terr as TerritoryId;
+        match conts.len() {
+            0 => problems.push(MapProblem::NoContinent(terr)),
+            1 => {}
+            _ => {
+                problems.push(MapProblem::OverlappingContinents {
+                    territory: terr,
+                    continents: conts,
+                })
+            }
+        }
+    }
+
+    problems
+}
+
+// groups the territories into pieces where each territory can reach every
+// other one. links are followed in both directions, since one-sided links are
+// reported separately
+fn connected_components(map: &dyn GameMap) -> Vec<Vec<TerritoryId>> {
+    let n = map.num_territories();
+
+    let mut links = vec![Vec::new(); n];
+    for a in 0..n {
+        for b in map.get_neighbors(a as TerritoryId) {
+            if (b as usize) < n {
+                links[a].push(b as usize);
+                links[b as usize].push(a);
+            }
+        }
+    }
+
+    let mut component_of = vec![None; n];
+    let mut components = Vec::new();
+    for start in 0..n {
+        if component_of[start].is_some() {
+            continue;
+        }
+
+        let id = components.len();
+        let mut component = Vec::new();
+        let mut stack = vec![start];
+        component_of[start] = Some(id);
+        while let Some(terr) = stack.pop() {
+            component.push(terr as TerritoryId);
+            for &next in links[terr].iter() {
+                if component_of[next].is_none() {
+                    component_of[next] = Some(id);
+                    stack.push(next);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    components
+}
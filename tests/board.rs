@@ -0,0 +1,222 @@
+extern crate wolfrisk;
+
+use wolfrisk::{GameBoard, GameMap, Rules, StandardGameBoard, standard_map};
+use wolfrisk::{PlayerId, TerritoryId, NumArmies};
+
+const AFRICA: &[&str] = &["Congo", "East Africa", "Egypt", "Madagascar", "North Africa",
+                          "South Africa"];
+const ASIA: &[&str] = &["Afghanistan", "China", "India", "Irkutsk", "Japan", "Kamchatka",
+                        "Middle East", "Mongolia", "Siam", "Siberia", "Ural", "Yakutsk"];
+const AUSTRALIA: &[&str] = &["Eastern Australia", "Indonesia", "New Guinea", "Western Australia"];
+const EUROPE: &[&str] = &["Great Britain", "Iceland", "Northern Europe", "Scandinavia",
+                          "Southern Europe", "Ukraine", "Western Europe"];
+const SOUTH_AMERICA: &[&str] = &["Argentina", "Brazil", "Peru", "Venezuela"];
+
+// who owns what on a test board. every territory not listed belongs to
+// player 1
+enum Owned {
+    // player 0 owns these territories
+    Named(Vec<&'static str>),
+    // player 0 owns this many territories, none of which complete a continent
+    Scattered(usize),
+    // player 0 owns every territory
+    All,
+}
+
+fn board_with(owned: &Owned) -> StandardGameBoard {
+    let map = standard_map();
+    let num_territories = map.num_territories();
+
+    let mut territories = vec![(1, 1); num_territories];
+    let player0: Vec<TerritoryId> = match *owned {
+        Owned::Named(ref names) => {
+            names.iter()
+                 .map(|name| map.territory_id(name).expect("unknown territory"))
+                 .collect()
+        }
+        Owned::Scattered(count) => {
+            // leave the first territory of every continent to player 1
+            let firsts: Vec<_> = map.continents().iter().map(|c| c.territories[0]).collect();
+            let spread: Vec<_> = (0..num_territories as TerritoryId)
+                                     .filter(|terr| !firsts.contains(terr))
+                                     .take(count)
+                                     .collect();
+            assert_eq!(spread.len(), count, "can't scatter {} territories", count);
+            spread
+        }
+        Owned::All => (0..num_territories as TerritoryId).collect(),
+    };
+    for terr in player0 {
+        territories[terr as usize] = (0, 1);
+    }
+
+    StandardGameBoard::new(2, territories, map).unwrap()
+}
+
+fn names(groups: &[&[&'static str]]) -> Owned {
+    Owned::Named(groups.iter().flat_map(|group| group.iter().cloned()).collect())
+}
+
+#[test]
+fn territory_reinforcements() {
+    let cases = [
+        // (description, territories owned, expected reinforcements)
+        ("no territories", 0, 3),
+        ("one territory", 1, 3),
+        ("just under 12", 11, 3),
+        ("12 territories", 12, 4),
+        ("13 territories", 13, 4),
+        ("14 territories", 14, 4),
+        ("15 territories", 15, 5),
+        ("20 territories", 20, 6),
+        ("30 territories", 30, 10),
+        ("36 territories", 36, 12),
+    ];
+
+    let rules = Rules::classic();
+    for &(description, owned, expected) in cases.iter() {
+        let board = board_with(&Owned::Scattered(owned));
+        assert_eq!(board.get_num_owned_territories(0) as usize, owned, "{}", description);
+        assert_eq!(board.get_continent_bonuses(0), 0, "{}", description);
+        assert_eq!(board.get_territory_reinforcements(0, &rules),
+                   expected,
+                   "{}",
+                   description);
+    }
+}
+
+#[test]
+fn reinforcements_with_continents() {
+    let cases = [
+        // (description, territories owned, expected reinforcements)
+        ("Australia", names(&[AUSTRALIA]), 3 + 2),
+        ("Australia less one", names(&[&AUSTRALIA[1..]]), 3),
+        ("South America", names(&[SOUTH_AMERICA]), 3 + 2),
+        ("Australia and South America", names(&[AUSTRALIA, SOUTH_AMERICA]), 3 + 4),
+        ("Africa", names(&[AFRICA]), 3 + 3),
+        ("Europe", names(&[EUROPE]), 3 + 5),
+        ("Asia", names(&[ASIA]), 4 + 7),
+        ("Africa and Europe", names(&[AFRICA, EUROPE]), 4 + 8),
+        ("Asia and Australia", names(&[ASIA, AUSTRALIA]), 5 + 9),
+        ("Asia, Africa and Europe", names(&[ASIA, AFRICA, EUROPE]), 8 + 15),
+        ("the whole world", Owned::All, 14 + 24),
+    ];
+
+    let rules = Rules::classic();
+    for &(description, ref owned, expected) in cases.iter() {
+        let board = board_with(owned);
+        let territories = board.get_num_owned_territories(0) as NumArmies;
+        let bonuses = board.get_continent_bonuses(0);
+        assert_eq!(rules.territory_reinforcements(territories) + bonuses,
+                   expected,
+                   "{}",
+                   description);
+        assert_eq!(board.get_territory_reinforcements(0, &rules),
+                   expected,
+                   "{}",
+                   description);
+    }
+}
+
+#[test]
+fn reinforcements_under_other_rules() {
+    let custom_bonuses = Rules {
+        // Africa, Asia, Australia, Europe, North America, South America
+        continent_bonuses: Some(vec![1, 2, 10, 4, 5, 6]),
+        ..Rules::classic()
+    };
+    let per_two = Rules { territories_per_army: 2, min_reinforcement: 2, ..Rules::classic() };
+
+    let cases = [
+        // (description, rules, territories owned, expected reinforcements)
+        ("no bonuses, Australia", Rules::no_continent_bonuses(), names(&[AUSTRALIA]), 3),
+        ("no bonuses, Asia", Rules::no_continent_bonuses(), names(&[ASIA]), 4),
+        ("no bonuses, everything", Rules::no_continent_bonuses(), Owned::All, 14),
+        ("custom bonuses, Australia", custom_bonuses.clone(), names(&[AUSTRALIA]), 3 + 10),
+        ("custom bonuses, Asia", custom_bonuses.clone(), names(&[ASIA]), 4 + 2),
+        ("custom bonuses, everything", custom_bonuses, Owned::All, 14 + 28),
+        ("one per two, 1 territory", per_two.clone(), Owned::Scattered(1), 2),
+        ("one per two, 9 territories", per_two.clone(), Owned::Scattered(9), 4),
+        ("one per two, Australia", per_two, names(&[AUSTRALIA]), 2 + 2),
+    ];
+
+    for &(description, ref rules, ref owned, expected) in cases.iter() {
+        let board = board_with(owned);
+        assert_eq!(board.get_territory_reinforcements(0, rules),
+                   expected,
+                   "{}",
+                   description);
+    }
+}
+
+#[test]
+fn continent_ownership() {
+    let cases = [
+        // (description, territories owned, continent, owned by player 0)
+        ("all of Australia", names(&[AUSTRALIA]), "Australia", true),
+        ("all but one of Australia", names(&[&AUSTRALIA[..3]]), "Australia", false),
+        ("Australia, asking about Asia", names(&[AUSTRALIA]), "Asia", false),
+        ("Asia and Australia, asking about Asia", names(&[ASIA, AUSTRALIA]), "Asia", true),
+        ("one territory of Africa", names(&[&AFRICA[..1]]), "Africa", false),
+        ("nothing", Owned::Named(vec![]), "Europe", false),
+        ("everything, asking about Europe", Owned::All, "Europe", true),
+        ("everything, asking about North America", Owned::All, "North America", true),
+        ("scattered territories", Owned::Scattered(36), "South America", false),
+    ];
+
+    for &(description, ref owned, continent, expected) in cases.iter() {
+        let board = board_with(owned);
+        let continent = board.continents()
+                             .iter()
+                             .find(|c| c.name == continent)
+                             .expect("unknown continent");
+        assert_eq!(board.player_owns_continent(0, continent), expected, "{}", description);
+    }
+}
+
+#[test]
+fn game_over() {
+    let cases: [(&str, Vec<PlayerId>, bool, &[PlayerId]); 6] = [
+        // (description, owners of the first territories, with player 0
+        //  owning the rest, game over, defeated players)
+        ("one player owns everything", vec![], true, &[1, 2]),
+        ("another player holds one territory", vec![1], false, &[2]),
+        ("two others hold one each", vec![1, 2], false, &[]),
+        ("one other player holds the first two", vec![2, 2], false, &[1]),
+        ("player 1 holds everything but the last", vec![1; 41], false, &[2]),
+        ("player 1 holds everything", vec![1; 42], true, &[0, 2]),
+    ];
+
+    for &(description, ref owners, over, defeated) in cases.iter() {
+        let map = standard_map();
+        let mut territories = vec![(0, 1); map.num_territories()];
+        for (terr, &owner) in owners.iter().enumerate() {
+            territories[terr].0 = owner;
+        }
+        let board = StandardGameBoard::new(3, territories, map).unwrap();
+
+        assert_eq!(board.game_is_over(), over, "{}", description);
+        for player in 0..3 {
+            assert_eq!(board.player_is_defeated(player),
+                       defeated.contains(&player),
+                       "{}: player {}",
+                       description,
+                       player);
+        }
+    }
+}
+
+#[test]
+fn game_over_after_conquest() {
+    let map = standard_map();
+    let mut territories = vec![(0, 1); map.num_territories()];
+    territories[5] = (1, 3);
+    let mut board = StandardGameBoard::new(2, territories, map).unwrap();
+    assert!(!board.game_is_over());
+    assert!(!board.player_is_defeated(1));
+
+    board.set_territory(5, 0, 1).unwrap();
+    assert!(board.game_is_over());
+    assert!(board.player_is_defeated(1));
+    assert_eq!(board.get_num_owned_territories(0), 42);
+}
@@ -0,0 +1,138 @@
+extern crate wolfrisk;
+
+use wolfrisk::{Card, CardManager, GameEvent, GameManager, GameManagerBuilder, GameSnapshot, Player};
+use wolfrisk::{GameMap, Rules, seeded_rng, standard_map};
+use wolfrisk::player::{HeuristicPlayer, RandomPlayer};
+use wolfrisk::snapshot::SNAPSHOT_VERSION;
+
+// a finished game between two random players and a heuristic one, with the
+// players and the game both seeded from `seed`
+fn seeded_game(seed: u64, rules: Rules) -> GameManager {
+    let mut rng = seeded_rng(seed);
+    let mut players = RandomPlayer::make_random_players(2, &mut rng);
+    players.extend(HeuristicPlayer::make_heuristic_players(1));
+    let mut mgr = GameManagerBuilder::new()
+                      .players(players)
+                      .rules(rules)
+                      .rng(rng)
+                      .build()
+                      .unwrap();
+    mgr.run().unwrap();
+    mgr
+}
+
+#[test]
+fn same_seed_same_game() {
+    for seed in 0..5 {
+        for rules in [Rules::classic(), Rules::progressive(), Rules::connected_fortify()].iter() {
+            let first = seeded_game(seed, rules.clone());
+            let second = seeded_game(seed, rules.clone());
+            assert!(first.events().len() > 1, "seed {}: nothing happened", seed);
+            assert_eq!(first.events(), second.events(), "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn different_seeds_different_games() {
+    assert!(seeded_game(1, Rules::classic()).events() != seeded_game(2, Rules::classic()).events());
+}
+
+// a game about to start player 0's turn, in which they can wipe out player 1,
+// who holds `victim_cards` cards and a single army on Madagascar. player 0
+// holds two cards, too few to trade before attacking, and owns everything
+// else but Argentina, where player 2 sits out of reach
+fn elimination_game(victim_cards: usize) -> GameManager {
+    let map = standard_map();
+    let madagascar = map.territory_id("Madagascar").unwrap();
+    let argentina = map.territory_id("Argentina").unwrap();
+    let mut territories = vec![(0, 10); map.num_territories()];
+    territories[madagascar as usize] = (1, 1);
+    territories[argentina as usize] = (2, 500);
+
+    let mut rng = seeded_rng(3);
+    let mut cards = CardManager::new(3, vec![Card::Wild; 10]);
+    for _ in 0..2 {
+        cards.draw_random_for_player(0, &mut rng).unwrap();
+    }
+    for _ in 0..victim_cards {
+        cards.draw_random_for_player(1, &mut rng).unwrap();
+    }
+
+    let snapshot = GameSnapshot {
+        version: SNAPSHOT_VERSION,
+        map: map.to_definition(),
+        rules: Rules::classic(),
+        num_players: 3,
+        neutral: None,
+        territories,
+        cards,
+        trades: 0,
+        curr_player: 0,
+        turn: 1,
+        forfeited: Vec::new(),
+    };
+    let mut players: Vec<Box<dyn Player>> = HeuristicPlayer::make_heuristic_players(1);
+    players.extend(RandomPlayer::make_random_players(2, &mut rng));
+    GameManagerBuilder::new()
+        .players(players)
+        .restore(snapshot)
+        .unwrap()
+        .rng(rng)
+        .build()
+        .unwrap()
+}
+
+// the events from player 1 being eliminated by player 0 until player 0
+// goes on attacking, or their turn ends
+fn after_elimination(mgr: &mut GameManager) -> Vec<GameEvent> {
+    assert!(mgr.play_turn().unwrap(), "the game ended");
+    let events = mgr.events();
+    let eliminated = GameEvent::PlayerEliminated { player: 1, by: 0 };
+    let start = events.iter()
+                      .position(|event| *event == eliminated)
+                      .expect("player 1 wasn't eliminated");
+    events[start + 1..]
+        .iter()
+        .take_while(|event| {
+            !matches!(**event,
+                      GameEvent::BattleResolved { .. } | GameEvent::Fortified { .. } |
+                      GameEvent::TurnStarted { .. })
+        })
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn eliminated_players_cards_are_taken_and_traded() {
+    let mut mgr = elimination_game(4);
+    let events = after_elimination(&mut mgr);
+
+    assert_eq!(events[0],
+               GameEvent::CardsCaptured { player: 0, from: 1, cards: vec![Card::Wild; 4] });
+    let trades: Vec<_> = events.iter()
+                               .filter(|event| match **event {
+                                   GameEvent::Trade { player, .. } => player == 0,
+                                   _ => false,
+                               })
+                               .collect();
+    // six cards force a trade, which leaves three: too few to have to go on
+    assert_eq!(trades.len(), 1, "{:?}", events);
+    assert!(events.iter().any(|event| match *event {
+        GameEvent::Reinforced { player, .. } => player == 0,
+        _ => false,
+    }));
+    assert_eq!(mgr.snapshot().cards.get_num_player_cards(1).unwrap(), 0);
+}
+
+#[test]
+fn no_trade_below_elimination_hand_size() {
+    // five cards are one short of a forced trade in the middle of an attack
+    let mut mgr = elimination_game(3);
+    let events = after_elimination(&mut mgr);
+
+    assert_eq!(events[0],
+               GameEvent::CardsCaptured { player: 0, from: 1, cards: vec![Card::Wild; 3] });
+    let traded = events.iter().any(|event| matches!(*event, GameEvent::Trade { .. }));
+    assert!(!traded, "{:?}", events);
+}
@@ -0,0 +1,52 @@
+extern crate wolfrisk;
+
+use wolfrisk::OddsCalculator;
+
+const EPSILON: f64 = 1e-12;
+
+fn assert_close(actual: f64, expected: f64, what: &str) {
+    assert!((actual - expected).abs() < EPSILON,
+            "{}: expected {}, got {}",
+            what,
+            expected,
+            actual);
+}
+
+#[test]
+fn one_on_one() {
+    // the attacker has to roll higher: 15 of the 36 rolls
+    let odds = OddsCalculator::new();
+    assert_close(odds.win_probability(1, 1), 15. / 36., "attacker wins");
+
+    let battle = odds.battle(1, 1);
+    assert_close(battle.attacker_left[1], 15. / 36., "one attacker left");
+    assert_close(battle.defender_left[1], 21. / 36., "one defender left");
+    assert_close(battle.expected_attacker_losses(), 21. / 36., "attacker losses");
+    assert_close(battle.expected_defender_losses(), 15. / 36., "defender losses");
+}
+
+#[test]
+fn three_dice_against_two() {
+    let odds = OddsCalculator::new();
+    let round = odds.round(3, 2);
+    let expected = [(0, 2, 2890.), (1, 1, 2611.), (2, 0, 2275.)];
+    assert_eq!(round.len(), expected.len());
+    for (&(attacker_losses, defender_losses, p), &(a, d, count)) in round.iter().zip(expected.iter()) {
+        assert_eq!((attacker_losses, defender_losses), (a, d));
+        assert_close(p,
+                     count / 7776.,
+                     &format!("attacker loses {}, defender loses {}", a, d));
+    }
+}
+
+#[test]
+fn two_on_one() {
+    // two dice beat one in 125 of the 216 rolls. if they don't, the last
+    // attacker gets a one-on-one try
+    let odds = OddsCalculator::new();
+    let two_dice = 125. / 216.;
+    let one_die = 15. / 36.;
+    assert_close(odds.win_probability(2, 1),
+                 two_dice + (1. - two_dice) * one_die,
+                 "attacker wins");
+}
@@ -0,0 +1,44 @@
+extern crate wolfrisk;
+
+use wolfrisk::{GameBoard, GameManagerBuilder, Replay, Rules, SetupMode, TerritoryId};
+use wolfrisk::seeded_rng;
+use wolfrisk::player::RandomPlayer;
+
+#[test]
+fn replay_matches_the_game() {
+    let setups = [(2, SetupMode::Random, Rules::classic()),
+                  (3, SetupMode::Draft, Rules::progressive()),
+                  (4, SetupMode::Random, Rules::connected_fortify())];
+    for seed in 0..5 {
+        for &(num_players, mode, ref rules) in setups.iter() {
+            let mut rng = seeded_rng(seed);
+            let mut mgr = GameManagerBuilder::new()
+                              .players(RandomPlayer::make_random_players(num_players, &mut rng))
+                              .setup_mode(mode)
+                              .rules(rules.clone())
+                              .rng(rng)
+                              .build()
+                              .unwrap();
+            mgr.run().unwrap();
+
+            let mut replay = Replay::new(mgr.events().to_vec()).unwrap();
+            replay.run_to_end().unwrap();
+            assert!(replay.is_finished());
+            for terr in 0..mgr.board().game_map().num_territories() {
+                let terr = terr as TerritoryId;
+                assert_eq!(replay.board().get_owner(terr),
+                           mgr.board().get_owner(terr),
+                           "seed {}, {} players: owner of {}",
+                           seed,
+                           num_players,
+                           terr);
+                assert_eq!(replay.board().get_num_armies(terr),
+                           mgr.board().get_num_armies(terr),
+                           "seed {}, {} players: armies on {}",
+                           seed,
+                           num_players,
+                           terr);
+            }
+        }
+    }
+}
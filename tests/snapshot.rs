@@ -0,0 +1,69 @@
+extern crate wolfrisk;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use wolfrisk::{GameManager, GameManagerBuilder, GameSnapshot, Rules, SnapshotFormat, seeded_rng};
+use wolfrisk::{PlayerId, TerritoryId};
+use wolfrisk::player::RandomPlayer;
+
+const NUM_PLAYERS: usize = 3;
+
+fn new_game(snapshot: Option<GameSnapshot>) -> GameManager {
+    let mut rng = seeded_rng(7);
+    let mut builder = GameManagerBuilder::new()
+                          .players(RandomPlayer::make_random_players(NUM_PLAYERS, &mut rng))
+                          .rules(Rules::progressive());
+    if let Some(snapshot) = snapshot {
+        builder = builder.restore(snapshot).unwrap();
+    }
+    builder.rng(rng).build().unwrap()
+}
+
+fn assert_same_game(restored: &GameManager, original: &GameManager, format: SnapshotFormat) {
+    let (before, after) = (original.snapshot(), restored.snapshot());
+    assert_eq!(after.territories, before.territories, "{:?}: board", format);
+    for terr in 0..original.board().game_map().num_territories() {
+        let terr = terr as TerritoryId;
+        assert_eq!(restored.board().get_owner(terr), original.board().get_owner(terr));
+        assert_eq!(restored.board().get_num_armies(terr), original.board().get_num_armies(terr));
+    }
+    for player in 0..NUM_PLAYERS as PlayerId {
+        assert_eq!(after.cards.get_player_cards(player).unwrap(),
+                   before.cards.get_player_cards(player).unwrap(),
+                   "{:?}: hand of player {}",
+                   format,
+                   player);
+    }
+    assert_eq!(after.cards.get_available(), before.cards.get_available(), "{:?}: deck", format);
+    assert_eq!(after.curr_player, before.curr_player, "{:?}: current player", format);
+    assert_eq!(restored.turn(), original.turn(), "{:?}: turn", format);
+    assert_eq!(after.trades, before.trades, "{:?}: trades", format);
+    assert_eq!(restored.rules(), original.rules(), "{:?}: rules", format);
+}
+
+#[test]
+fn round_trip() {
+    let mut original = new_game(None);
+    for _ in 0..25 {
+        assert!(original.play_turn().unwrap(), "the game ended too early");
+    }
+
+    let held: usize = (0..NUM_PLAYERS as PlayerId)
+        .map(|player| original.snapshot().cards.get_num_player_cards(player).unwrap())
+        .sum();
+    assert!(held > 0, "nobody holds any cards to restore");
+
+    let formats = [(SnapshotFormat::Json, "json"), (SnapshotFormat::Binary, "bin")];
+    for &(format, extension) in formats.iter() {
+        let name = format!("wolfrisk-snapshot-{}.{}", process::id(), extension);
+        let path = env::temp_dir().join(name);
+        original.snapshot().save(&path, format).unwrap();
+        let loaded = GameSnapshot::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let restored = new_game(Some(loaded.unwrap()));
+        assert_same_game(&restored, &original, format);
+    }
+}
@@ -0,0 +1,100 @@
+extern crate wolfrisk;
+
+use wolfrisk::{Continent, GameMap, MapProblem, TerritoryId, standard_map, validate_map};
+
+// a map given as the neighbor list of every territory, taken as is
+struct ListMap(Vec<Vec<TerritoryId>>);
+
+impl GameMap for ListMap {
+    fn are_adjacent(&self, a: TerritoryId, b: TerritoryId) -> bool {
+        self.0[a as usize].contains(&b)
+    }
+
+    fn get_neighbors(&self, terr: TerritoryId) -> Vec<TerritoryId> {
+        self.0[terr as usize].clone()
+    }
+
+    fn num_territories(&self) -> usize {
+        self.0.len()
+    }
+}
+
+// four territories in a ring: 0 - 1 - 2 - 3 - 0
+fn ring() -> Vec<Vec<TerritoryId>> {
+    vec![vec![1, 3], vec![0, 2], vec![1, 3], vec![2, 0]]
+}
+
+fn continents(members: &[&[TerritoryId]]) -> Vec<Continent> {
+    members.iter()
+           .enumerate()
+           .map(|(i, terrs)| {
+               Continent {
+                   name: format!("Continent {}", i),
+                   bonus: 2,
+                   territories: terrs.to_vec(),
+               }
+           })
+           .collect()
+}
+
+#[test]
+fn valid_maps() {
+    let map = standard_map();
+    assert_eq!(validate_map(&map, map.continents()), vec![]);
+    assert_eq!(validate_map(&ListMap(ring()), &continents(&[&[0, 1], &[2, 3]])), vec![]);
+}
+
+#[test]
+fn broken_links() {
+    let mut asymmetric = ring();
+    asymmetric[0].push(2);
+    let mut duplicate = ring();
+    duplicate[1].push(2);
+    duplicate[2].push(1);
+    let mut self_loop = ring();
+    self_loop[3].push(3);
+    let split = vec![vec![1], vec![0], vec![3], vec![2]];
+
+    let cases = vec![
+        ("asymmetric", asymmetric, vec![MapProblem::AsymmetricLink { from: 0, to: 2 }]),
+        ("duplicate edge",
+         duplicate,
+         vec![MapProblem::DuplicateEdge { from: 1, to: 2, count: 2 },
+              MapProblem::DuplicateEdge { from: 2, to: 1, count: 2 }]),
+        ("self loop", self_loop, vec![MapProblem::SelfLoop(3)]),
+        ("split", split, vec![MapProblem::Disconnected(vec![vec![0, 1], vec![2, 3]])]),
+    ];
+
+    let all = continents(&[&[0, 1, 2, 3]]);
+    for (description, neighbors, expected) in cases {
+        assert_eq!(validate_map(&ListMap(neighbors), &all), expected, "{}", description);
+    }
+}
+
+#[test]
+fn broken_continents() {
+    let cases: Vec<(&str, Vec<Continent>, Vec<MapProblem>)> = vec![
+        ("no continent", continents(&[&[0, 1, 2]]), vec![MapProblem::NoContinent(3)]),
+        ("overlapping",
+         continents(&[&[0, 1, 2], &[2, 3]]),
+         vec![MapProblem::OverlappingContinents { territory: 2, continents: vec![0, 1] }]),
+        ("empty", continents(&[&[0, 1, 2, 3], &[]]), vec![MapProblem::EmptyContinent(1)]),
+        ("unknown territory",
+         continents(&[&[0, 1, 2, 3, 7]]),
+         vec![MapProblem::UnknownTerritory { continent: 0, territory: 7 }]),
+        ("duplicate member",
+         continents(&[&[0, 1], &[2, 3, 2]]),
+         vec![MapProblem::DuplicateMember { continent: 1, territory: 2 }]),
+    ];
+
+    for (description, conts, expected) in cases {
+        assert_eq!(validate_map(&ListMap(ring()), &conts), expected, "{}", description);
+    }
+}
+
+#[test]
+fn descriptions_name_continents() {
+    let problems = validate_map(&ListMap(ring()), &continents(&[&[0, 1], &[2, 3, 2]]));
+    let description = problems[0].describe(&|t| format!("T{}", t), &|c| format!("C{}", c));
+    assert_eq!(description, "C1 lists T2 more than once");
+}